@@ -10,7 +10,9 @@ macro_rules! bit_size_of {
 
 /// `BitSet` is a set of `usize` values.
 /// `E`: Integer + BitOps is the element type of the storage
-/// `T`: Into<usize> + Copy is the element type of the set
+/// `T`: Into<usize> + Copy is the element type of the set. Implementing `Set` and `SetConstructor` also requires
+/// `T: TryFrom<usize>`, as `iter` and `retain` rebuild the elements from their bit indices; element types that only
+/// convert into `usize` can still use the inherent methods but no longer implement `Set`.
 /// `S` is the storage type, can either be `Vec<E>` or `[E; N]`
 #[cfg(feature = "std")]
 #[derive(Debug, Clone, Copy)]
@@ -46,9 +48,63 @@ pub trait BitSetView<E: Integer + BitOps>: AsRef<[E]> + AsMut<[E]> {
     }
 
     fn bit_is_subset_of(&self, other: &Self) -> bool {
-        self.as_ref()[other.as_ref().len()..].iter().all(|&x| x == E::zero()) &&
+        self.as_ref().get(other.as_ref().len()..).unwrap_or_default().iter().all(|&x| x == E::zero()) &&
         self.as_ref().iter().zip(other.as_ref().iter()).all(|(a, b)| *a & *b == *a)
     }
+
+    fn bit_is_disjoint(&self, other: &Self) -> bool {
+        self.as_ref().iter().zip(other.as_ref().iter()).all(|(a, b)| *a & *b == E::zero())
+    }
+
+    /// `other` must not be longer than `self`
+    fn bit_union_with(&mut self, other: &Self) {
+        self.as_mut().iter_mut().zip(other.as_ref().iter()).for_each(|(a, b)| *a |= *b)
+    }
+
+    fn bit_intersect_with(&mut self, other: &Self) {
+        let n = other.as_ref().len().min(self.as_ref().len());
+        self.as_mut().iter_mut().zip(other.as_ref().iter()).for_each(|(a, b)| *a &= *b);
+        self.as_mut()[n..].iter_mut().for_each(|a| *a = E::zero())
+    }
+
+    fn bit_difference_with(&mut self, other: &Self) {
+        self.as_mut().iter_mut().zip(other.as_ref().iter()).for_each(|(a, b)| *a &= !*b)
+    }
+
+    fn bit_count(&self) -> usize {
+        self.as_ref().iter().map(|&x| x.count_ones() as usize).sum()
+    }
+
+    fn bit_clear(&mut self) {
+        self.as_mut().iter_mut().for_each(|x| *x = E::zero())
+    }
+
+    /// iterates the indices of the set bits in ascending order
+    fn bit_iter<'a>(&'a self) -> impl Iterator<Item = usize> + 'a where E: 'a {
+        self.as_ref().iter().enumerate().filter(|(_, x)| **x != E::zero()).flat_map(|(element_index, &x)| {
+            (0..bit_size_of!(E))
+                .filter(move |&bit_offset| x & (E::one() << bit_offset) != E::zero())
+                .map(move |bit_offset| element_index * bit_size_of!(E) + bit_offset)
+        })
+    }
+
+    /// unsets the bits whose indices do not satisfy `f`
+    fn bit_retain(&mut self, mut f: impl FnMut(usize) -> bool) {
+        for element_index in 0..self.as_ref().len() {
+            if self.as_ref()[element_index] == E::zero() {
+                continue
+            }
+            for bit_offset in 0..bit_size_of!(E) {
+                if self.bit_get(element_index, bit_offset) && !f(element_index * bit_size_of!(E) + bit_offset) {
+                    self.bit_unset(element_index, bit_offset);
+                }
+            }
+        }
+    }
+}
+
+fn from_bit_index<T: TryFrom<usize>>(index: usize) -> T {
+    T::try_from(index).unwrap_or_else(|_| unreachable!("bit index {index} does not fit in the element type"))
 }
 
 impl<E: Integer + BitOps> BitSetView<E> for [E] {}
@@ -74,7 +130,7 @@ impl<E: Integer + BitOps, T: Into<usize> + Copy> Default for BitSet<T, Vec<E>> {
 }
 
 #[cfg(feature = "std")]
-impl<E: Integer + BitOps, T: Into<usize> + Copy> Set<T> for BitSet<T, Vec<E>> {
+impl<E: Integer + BitOps, T: Into<usize> + TryFrom<usize> + Copy> Set<T> for BitSet<T, Vec<E>> {
     fn insert(&mut self, index: T) -> bool {
         let (element_index, bit_offset) = self.0.bit_index(index.into());
         if element_index >= self.0.len() {
@@ -102,10 +158,50 @@ impl<E: Integer + BitOps, T: Into<usize> + Copy> Set<T> for BitSet<T, Vec<E>> {
         }
         self.0.bit_get(element_index, bit_offset)
     }
+
+    fn len(&self) -> usize {
+        self.0.bit_count()
+    }
+
+    fn clear(&mut self) {
+        self.0.bit_clear()
+    }
+
+    fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        self.0.bit_retain(|index| f(&from_bit_index(index)))
+    }
+
+    #[allow(refining_impl_trait)]
+    fn iter<'a>(&'a self) -> impl Iterator<Item = T> + 'a where T: 'a {
+        self.0.bit_iter().map(from_bit_index)
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        if self.0.len() < other.0.len() {
+            self.0.resize(other.0.len(), E::zero());
+        }
+        self.0.bit_union_with(&other.0)
+    }
+
+    fn intersect_with(&mut self, other: &Self) {
+        self.0.bit_intersect_with(&other.0)
+    }
+
+    fn difference_with(&mut self, other: &Self) {
+        self.0.bit_difference_with(&other.0)
+    }
+
+    fn is_subset(&self, other: &Self) -> bool {
+        self.0.bit_is_subset_of(&other.0)
+    }
+
+    fn is_disjoint(&self, other: &Self) -> bool {
+        self.0.bit_is_disjoint(&other.0)
+    }
 }
 
 #[cfg(feature = "std")]
-impl<E: Integer + BitOps, T: Into<usize> + Copy> FromIterator<T> for BitSet<T, Vec<E>> {
+impl<E: Integer + BitOps, T: Into<usize> + TryFrom<usize> + Copy> FromIterator<T> for BitSet<T, Vec<E>> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let mut set = Self::new();
         for i in iter {
@@ -115,6 +211,15 @@ impl<E: Integer + BitOps, T: Into<usize> + Copy> FromIterator<T> for BitSet<T, V
     }
 }
 
+#[cfg(feature = "std")]
+impl<E: Integer + BitOps, T: Into<usize> + TryFrom<usize> + Copy> Extend<T> for BitSet<T, Vec<E>> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for i in iter {
+            self.insert(i);
+        }
+    }
+}
+
 #[cfg(feature = "std")]
 impl<E: Integer + BitOps, T: Into<usize> + Copy> BitSet<T, Vec<E>> {
     pub fn is_subset_of(&self, other: &Self) -> bool {
@@ -138,7 +243,7 @@ impl<E: Integer + BitOps, T: Into<usize> + Copy, const N: usize> Default for Bit
     }
 }
 
-impl<E: Integer + BitOps, T: Into<usize> + Copy, const N: usize> FromIterator<T> for BitSet<T, [E; N]> {
+impl<E: Integer + BitOps, T: Into<usize> + TryFrom<usize> + Copy, const N: usize> FromIterator<T> for BitSet<T, [E; N]> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let mut set = Self::new();
         for i in iter {
@@ -148,7 +253,15 @@ impl<E: Integer + BitOps, T: Into<usize> + Copy, const N: usize> FromIterator<T>
     }
 }
 
-impl<E: Integer + BitOps, T: Into<usize> + Copy, const N: usize> Set<T> for BitSet<T, [E; N]> {
+impl<E: Integer + BitOps, T: Into<usize> + TryFrom<usize> + Copy, const N: usize> Extend<T> for BitSet<T, [E; N]> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for i in iter {
+            self.insert(i);
+        }
+    }
+}
+
+impl<E: Integer + BitOps, T: Into<usize> + TryFrom<usize> + Copy, const N: usize> Set<T> for BitSet<T, [E; N]> {
     fn insert(&mut self, index: T) -> bool {
         let (element_index, bit_offset) = self.0.bit_index(index.into());
         let old = self.0.bit_get(element_index, bit_offset);
@@ -173,6 +286,43 @@ impl<E: Integer + BitOps, T: Into<usize> + Copy, const N: usize> Set<T> for BitS
         }
         self.0.bit_get(element_index, bit_offset)
     }
+
+    fn len(&self) -> usize {
+        self.0.bit_count()
+    }
+
+    fn clear(&mut self) {
+        self.0.bit_clear()
+    }
+
+    fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        self.0.bit_retain(|index| f(&from_bit_index(index)))
+    }
+
+    #[allow(refining_impl_trait)]
+    fn iter<'a>(&'a self) -> impl Iterator<Item = T> + 'a where T: 'a {
+        self.0.bit_iter().map(from_bit_index)
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        self.0.bit_union_with(&other.0)
+    }
+
+    fn intersect_with(&mut self, other: &Self) {
+        self.0.bit_intersect_with(&other.0)
+    }
+
+    fn difference_with(&mut self, other: &Self) {
+        self.0.bit_difference_with(&other.0)
+    }
+
+    fn is_subset(&self, other: &Self) -> bool {
+        self.0.bit_is_subset_of(&other.0)
+    }
+
+    fn is_disjoint(&self, other: &Self) -> bool {
+        self.0.bit_is_disjoint(&other.0)
+    }
 }
 
 impl<E: Integer + BitOps, T: Into<usize> + Copy, const N: usize> BitSet<T, [E; N]> {
@@ -192,17 +342,17 @@ pub struct BitSetConstructor<T = Vec<u32>>(core::marker::PhantomData<T>);
 pub struct BitSetConstructor<T>(core::marker::PhantomData<T>);
 
 #[cfg(feature = "std")]
-impl<E: Integer + BitOps, T: Into<usize> + Copy> SetConstructor<T> for BitSetConstructor<Vec<E>> {
+impl<E: Integer + BitOps, T: Into<usize> + TryFrom<usize> + Copy> SetConstructor<T> for BitSetConstructor<Vec<E>> {
     type Set = BitSet<T, Vec<E>>;
 }
 
-impl<E: Integer + BitOps, T: Into<usize> + Copy, const N: usize> SetConstructor<T> for BitSetConstructor<[E; N]> {
+impl<E: Integer + BitOps, T: Into<usize> + TryFrom<usize> + Copy, const N: usize> SetConstructor<T> for BitSetConstructor<[E; N]> {
     type Set = BitSet<T, [E; N]>;
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{new_index_type, ArraySet, ArraySetConstructor};
+    use crate::{new_index_type, ArraySet, ArraySetConstructor, ArrayVec};
     #[cfg(feature = "std")]
     use crate::{HashSetConstructor, BTreeSetConstructor};
     use super::*;
@@ -222,6 +372,17 @@ mod tests {
         assert!(set.is_empty());
     }
 
+    #[test]
+    fn test_bitset_iter() {
+        let mut set: BitSet<usize, [i8; 4]> = [0, 7, 8, 31, 13].into_iter().collect();
+        assert_eq!(set.len(), 5);
+        assert_eq!(set.iter().collect::<ArrayVec<_, 5>>()[..], [0, 7, 8, 13, 31]);
+        set.retain(|x| x % 2 == 1);
+        assert_eq!(set.iter().collect::<ArrayVec<_, 5>>()[..], [7, 13, 31]);
+        set.clear();
+        assert!(set.is_empty());
+    }
+
     #[test]
     fn test_bitset_with_set_constructor_and_index_types() {
         new_index_type!(Foo);
@@ -285,4 +446,27 @@ mod tests {
         assert!(a.is_subset_of(&b));
         assert!(!b.is_subset_of(&a));
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_bitset_algebra_with_different_lengths() {
+        let short: BitSet<usize> = [1, 2].into_iter().collect();
+        let long: BitSet<usize> = [2, 100].into_iter().collect();
+        assert!(!short.is_subset(&long) && !long.is_subset(&short));
+        assert!(!short.is_disjoint(&long));
+
+        let mut a = short.clone();
+        a.union_with(&long);
+        assert_eq!(a.iter().collect::<Vec<_>>(), [1, 2, 100]);
+        assert!(short.is_subset(&a) && long.is_subset(&a));
+
+        let mut b = long.clone();
+        b.intersect_with(&short);
+        assert_eq!(b.iter().collect::<Vec<_>>(), [2]);
+
+        let mut c = long.clone();
+        c.difference_with(&short);
+        assert_eq!(c.iter().collect::<Vec<_>>(), [100]);
+        assert!(c.is_disjoint(&short));
+    }
 }
@@ -0,0 +1,163 @@
+use super::{Map, MapConstructor, ArrayVec};
+#[cfg(feature = "std")]
+use super::HashMapConstructor;
+
+/// A one-to-one map that supports lookups in both directions.
+/// Both sides are stored in maps built by `C`, so `L` and `R` are cloned once on insertion.
+#[cfg(feature = "std")]
+pub struct BiMap<L, R, C: MapConstructor<L> + MapConstructor<R> = HashMapConstructor> {
+    left_to_right: <C as MapConstructor<L>>::Map<R>,
+    right_to_left: <C as MapConstructor<R>>::Map<L>,
+}
+
+#[cfg(not(feature = "std"))]
+pub struct BiMap<L, R, C: MapConstructor<L> + MapConstructor<R>> {
+    left_to_right: <C as MapConstructor<L>>::Map<R>,
+    right_to_left: <C as MapConstructor<R>>::Map<L>,
+}
+
+impl<L: Clone, R: Clone, C: MapConstructor<L> + MapConstructor<R>> BiMap<L, R, C> {
+    pub fn new() -> Self {
+        BiMap {
+            left_to_right: <C as MapConstructor<L>>::new(),
+            right_to_left: <C as MapConstructor<R>>::new(),
+        }
+    }
+
+    /// Inserts the pair if neither side is already associated with something else.
+    /// Inserting an existing pair again is a no-op. Returns the pair back on conflicts.
+    pub fn insert(&mut self, left: L, right: R) -> Result<(), (L, R)> where L: PartialEq, R: PartialEq {
+        match (self.left_to_right.get(&left), self.right_to_left.get(&right)) {
+            (None, None) => {
+                self.left_to_right.insert(left.clone(), right.clone());
+                self.right_to_left.insert(right, left);
+                Ok(())
+            }
+            (Some(r), Some(l)) if *r == right && *l == left => Ok(()),
+            _ => Err((left, right)),
+        }
+    }
+
+    /// Inserts the pair, removing any existing pairs that conflict with it. Returns the removed pairs,
+    /// none if the pair is already present.
+    pub fn insert_overwrite(&mut self, left: L, right: R) -> ArrayVec<(L, R), 2> where R: PartialEq {
+        if self.left_to_right.get(&left) == Some(&right) {
+            return ArrayVec::new()
+        }
+        let removed = self.remove_by_left(&left).into_iter().chain(self.remove_by_right(&right)).collect();
+        self.left_to_right.insert(left.clone(), right.clone());
+        self.right_to_left.insert(right, left);
+        removed
+    }
+
+    pub fn get_by_left(&self, left: &L) -> Option<&R> {
+        self.left_to_right.get(left)
+    }
+
+    pub fn get_by_right(&self, right: &R) -> Option<&L> {
+        self.right_to_left.get(right)
+    }
+
+    pub fn contains_left(&self, left: &L) -> bool {
+        self.left_to_right.contains_key(left)
+    }
+
+    pub fn contains_right(&self, right: &R) -> bool {
+        self.right_to_left.contains_key(right)
+    }
+
+    pub fn remove_by_left(&mut self, left: &L) -> Option<(L, R)> {
+        let (left, right) = self.left_to_right.remove_entry(left)?;
+        self.right_to_left.remove(&right);
+        Some((left, right))
+    }
+
+    pub fn remove_by_right(&mut self, right: &R) -> Option<(L, R)> {
+        let (right, left) = self.right_to_left.remove_entry(right)?;
+        self.left_to_right.remove(&left);
+        Some((left, right))
+    }
+
+    pub fn len(&self) -> usize {
+        self.left_to_right.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.left_to_right.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.left_to_right.clear();
+        self.right_to_left.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&L, &R)> {
+        self.left_to_right.iter()
+    }
+
+    pub fn left_values(&self) -> impl Iterator<Item = &L> {
+        self.left_to_right.keys()
+    }
+
+    pub fn right_values(&self) -> impl Iterator<Item = &R> {
+        self.right_to_left.keys()
+    }
+}
+
+impl<L: Clone, R: Clone, C: MapConstructor<L> + MapConstructor<R>> Default for BiMap<L, R, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: Clone, R: Clone, C: MapConstructor<L> + MapConstructor<R>> Clone for BiMap<L, R, C> where
+    <C as MapConstructor<L>>::Map<R>: Clone,
+    <C as MapConstructor<R>>::Map<L>: Clone
+{
+    fn clone(&self) -> Self {
+        BiMap {
+            left_to_right: self.left_to_right.clone(),
+            right_to_left: self.right_to_left.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArrayMapConstructor;
+    #[cfg(feature = "std")]
+    use crate::BTreeMapConstructor;
+
+    #[test]
+    fn test_bimap() {
+        fn foo<C: MapConstructor<&'static str> + MapConstructor<usize>>() {
+            let mut symbols: BiMap<&str, usize, C> = BiMap::new();
+            assert!(symbols.insert("x", 0).is_ok());
+            assert!(symbols.insert("y", 1).is_ok());
+            assert!(symbols.insert("x", 0).is_ok());
+            assert_eq!(symbols.insert("x", 2), Err(("x", 2)));
+            assert_eq!(symbols.insert("z", 1), Err(("z", 1)));
+            assert_eq!(symbols.len(), 2);
+
+            assert_eq!(symbols.get_by_left(&"y"), Some(&1));
+            assert_eq!(symbols.get_by_right(&0), Some(&"x"));
+            assert!(!symbols.contains_right(&2));
+
+            assert_eq!(symbols.insert_overwrite("x", 1)[..], [("x", 0), ("y", 1)]);
+            assert!(symbols.insert_overwrite("x", 1).is_empty());
+            assert_eq!(symbols.len(), 1);
+            assert_eq!(symbols.get_by_right(&1), Some(&"x"));
+            assert!(!symbols.contains_left(&"y") && !symbols.contains_right(&0));
+
+            assert_eq!(symbols.remove_by_right(&1), Some(("x", 1)));
+            assert!(symbols.is_empty() && !symbols.contains_left(&"x"));
+        }
+
+        #[cfg(feature = "std")]
+        foo::<HashMapConstructor>();
+        #[cfg(feature = "std")]
+        foo::<BTreeMapConstructor>();
+        foo::<ArrayMapConstructor<3>>();
+    }
+}
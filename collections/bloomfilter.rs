@@ -0,0 +1,151 @@
+use core::hash::{BuildHasher, Hash};
+use crate::BitSetView;
+use super::double_hashing;
+
+// k bit positions are derived from a single 64-bit hash with double hashing.
+// `S` is the storage, either `Vec<u64>` (sized from a target capacity and false positive rate) or `[u64; N]` for no_std.
+
+#[cfg(feature = "std")]
+pub struct BloomFilter<H: BuildHasher, S = Vec<u64>> {
+    build_hasher: H,
+    bits: S,
+    num_hashes: u32,
+}
+
+#[cfg(not(feature = "std"))]
+pub struct BloomFilter<H: BuildHasher, S> {
+    build_hasher: H,
+    bits: S,
+    num_hashes: u32,
+}
+
+/// the number of hash functions minimizing the false positive rate for `capacity` items in `num_bits` bits: (m / n) ln 2
+pub fn bloom_filter_optimal_num_hashes(num_bits: usize, capacity: usize) -> u32 {
+    ((num_bits as f64 / capacity.max(1) as f64 * core::f64::consts::LN_2 + 0.5) as u32).max(1)
+}
+
+#[cfg(feature = "std")]
+impl<H: BuildHasher> BloomFilter<H, Vec<u64>> {
+    /// Creates a filter holding `capacity` items with the target false positive rate, using
+    /// m = -n ln(p) / (ln 2)^2 bits.
+    pub fn new(build_hasher: H, capacity: usize, false_positive_rate: f64) -> Self {
+        debug_assert!(false_positive_rate > 0. && false_positive_rate < 1.);
+        let num_bits = (-(capacity.max(1) as f64) * false_positive_rate.ln() / (core::f64::consts::LN_2 * core::f64::consts::LN_2)).ceil() as usize;
+        let storage = vec![0; num_bits.div_ceil(64)];
+        let num_hashes = bloom_filter_optimal_num_hashes(storage.len() * 64, capacity);
+        Self::with_storage(build_hasher, storage, num_hashes)
+    }
+}
+
+impl<H: BuildHasher, const N: usize> BloomFilter<H, [u64; N]> {
+    /// Creates a filter with `N * 64` bits and the number of hashes optimal for `capacity` items.
+    pub fn new(build_hasher: H, capacity: usize) -> Self {
+        Self::with_storage(build_hasher, [0; N], bloom_filter_optimal_num_hashes(N * 64, capacity))
+    }
+}
+
+impl<H: BuildHasher, S: AsRef<[u64]> + AsMut<[u64]>> BloomFilter<H, S> {
+    pub fn with_storage(build_hasher: H, storage: S, num_hashes: u32) -> Self {
+        assert!(!storage.as_ref().is_empty() && num_hashes > 0);
+        Self { build_hasher, bits: storage, num_hashes }
+    }
+
+    /// Returns true if the item was definitely not present before.
+    pub fn insert(&mut self, v: impl Hash) -> bool {
+        let mut inserted = false;
+        let x = self.build_hasher.hash_one(v);
+        for position in double_hashing(x, self.num_hashes, self.num_bits()) {
+            let (element_index, bit_offset) = self.bits.as_ref().bit_index(position);
+            inserted |= !self.bits.as_ref().bit_get(element_index, bit_offset);
+            self.bits.as_mut().bit_set(element_index, bit_offset);
+        }
+        inserted
+    }
+
+    /// Returns false if the item is definitely not present; true if it is present with high probability.
+    pub fn contains(&self, v: impl Hash) -> bool {
+        double_hashing(self.build_hasher.hash_one(v), self.num_hashes, self.num_bits()).all(|position| {
+            let (element_index, bit_offset) = self.bits.as_ref().bit_index(position);
+            self.bits.as_ref().bit_get(element_index, bit_offset)
+        })
+    }
+
+    /// Merges another filter into this one. Both must have the same size, number of hashes and hasher.
+    pub fn union_with(&mut self, other: &Self) {
+        assert!(self.bits.as_ref().len() == other.bits.as_ref().len() && self.num_hashes == other.num_hashes);
+        self.bits.as_mut().bit_union_with(other.bits.as_ref())
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.as_mut().bit_clear()
+    }
+
+    pub fn num_bits(&self) -> usize {
+        self.bits.as_ref().len() * 64
+    }
+
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    /// The fraction of bits that are set.
+    pub fn fill_ratio(&self) -> f64 {
+        self.bits.as_ref().bit_count() as f64 / self.num_bits() as f64
+    }
+
+    /// The probability of a false positive given the current fill ratio.
+    pub fn false_positive_rate(&self) -> f64 {
+        let fill = self.fill_ratio();
+        (0..self.num_hashes).fold(1., |p, _| p * fill)
+    }
+
+    /// Estimates the number of inserted items from the fill ratio (Swamidass and Baldi, 2007).
+    #[cfg(feature = "std")]
+    pub fn estimated_len(&self) -> f64 {
+        -(self.num_bits() as f64) / self.num_hashes as f64 * (1. - self.fill_ratio()).ln()
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type DeterministicState = core::hash::BuildHasherDefault<std::hash::DefaultHasher>;
+
+    #[test]
+    fn test_bloom_filter() {
+        let mut filter = BloomFilter::<_>::new(DeterministicState::default(), 1000, 0.01);
+        assert_eq!(filter.num_hashes(), 7);
+        assert!(filter.num_bits() >= 9585);
+
+        let inserted = (0..1000).filter(|&i| filter.insert(i)).count();
+        assert!(inserted > 980); // an insertion can be a false positive itself
+        assert!(!filter.insert(0));
+        assert!((0..1000).all(|i| filter.contains(i)));
+        assert!((filter.estimated_len() - 1000.).abs() < 50.);
+        assert!(filter.false_positive_rate() < 0.015);
+
+        let false_positives = (1000..101000).filter(|&i| filter.contains(i)).count();
+        assert!(false_positives < 1500);
+    }
+
+    #[test]
+    fn test_bloom_filter_fixed_storage_union() {
+        let hasher = DeterministicState::default();
+        let mut a = BloomFilter::<_, [u64; 32]>::new(hasher.clone(), 200);
+        let mut b = BloomFilter::<_, [u64; 32]>::new(hasher, 200);
+        assert_eq!(a.num_hashes(), 7);
+
+        (0..100).for_each(|i| { a.insert(i); });
+        (100..200).for_each(|i| { b.insert(i); });
+        assert!(!a.contains(150) || !a.contains(151) || !a.contains(152));
+        a.union_with(&b);
+        assert!((0..200).all(|i| a.contains(i)));
+        assert!(a.fill_ratio() > b.fill_ratio());
+
+        a.clear();
+        assert_eq!(a.fill_ratio(), 0.);
+        assert!(!a.contains(0));
+    }
+}
@@ -0,0 +1,217 @@
+use super::{Map, MapConstructor};
+#[cfg(feature = "std")]
+use super::HashMapConstructor;
+#[cfg(feature = "std")]
+use crate::MinHeap;
+
+/// A multiset that counts the occurrences of each key.
+/// `C` chooses the map backend, e.g. `HashMapConstructor`, `BTreeMapConstructor` or `ArrayMapConstructor<N>`.
+#[cfg(feature = "std")]
+pub struct Counter<K, C: MapConstructor<K> = HashMapConstructor> {
+    counts: C::Map<usize>,
+    total: usize,
+}
+
+#[cfg(not(feature = "std"))]
+pub struct Counter<K, C: MapConstructor<K>> {
+    counts: C::Map<usize>,
+    total: usize,
+}
+
+impl<K, C: MapConstructor<K>> Counter<K, C> {
+    pub fn new() -> Self {
+        Counter { counts: C::new(), total: 0 }
+    }
+
+    pub fn add(&mut self, key: K) {
+        self.add_n(key, 1)
+    }
+
+    pub fn add_n(&mut self, key: K, n: usize) {
+        if n == 0 {
+            return
+        }
+        *self.counts.entry(key).or_insert(0) += n;
+        self.total += n;
+    }
+
+    /// Returns true if the key was present.
+    pub fn remove_one(&mut self, key: &K) -> bool {
+        self.remove_n(key, 1) == 1
+    }
+
+    /// Decreases the count of the key by at most `n` and returns the actual amount removed.
+    /// Keys whose count drops to zero are removed from the underlying map.
+    pub fn remove_n(&mut self, key: &K, n: usize) -> usize {
+        let Some(count) = self.counts.get_mut(key) else { return 0 };
+        let removed = n.min(*count);
+        *count -= removed;
+        if *count == 0 {
+            self.counts.remove(key);
+        }
+        self.total -= removed;
+        removed
+    }
+
+    pub fn count(&self, key: &K) -> usize {
+        self.counts.get(key).copied().unwrap_or(0)
+    }
+
+    /// The sum of all counts.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// The number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.clear();
+        self.total = 0;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, usize)> {
+        self.counts.iter().map(|(k, &v)| (k, v))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.counts.keys()
+    }
+
+    /// Adds the counts of `other` into `self`.
+    pub fn merge(&mut self, other: &Self) where K: Clone {
+        for (k, &v) in other.counts.iter() {
+            self.add_n(k.clone(), v)
+        }
+    }
+
+    /// Subtracts the counts of `other` from `self`, saturating at zero.
+    pub fn subtract(&mut self, other: &Self) {
+        for (k, &v) in other.counts.iter() {
+            self.remove_n(k, v);
+        }
+    }
+
+    pub fn into_map(self) -> C::Map<usize> {
+        self.counts
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, C: MapConstructor<K>> Counter<K, C> {
+    /// Returns the `k` most common keys with their counts in descending order. Ties are broken arbitrarily.
+    pub fn most_common(&self, k: usize) -> Vec<(&K, usize)> {
+        if k == 0 {
+            return vec![]
+        }
+
+        let mut heap = MinHeap::with_capacity(k + 1);
+        for (key, &count) in self.counts.iter() {
+            heap.push(key, count);
+            if heap.len() > k {
+                heap.pop();
+            }
+        }
+
+        let mut results = Vec::with_capacity(heap.len());
+        while let Some(entry) = heap.pop_with_priority() {
+            results.push(entry);
+        }
+        results.reverse();
+        results
+    }
+}
+
+impl<K, C: MapConstructor<K>> Default for Counter<K, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, C: MapConstructor<K>> Clone for Counter<K, C> where C::Map<usize>: Clone {
+    fn clone(&self) -> Self {
+        Counter { counts: self.counts.clone(), total: self.total }
+    }
+}
+
+impl<K, C: MapConstructor<K>> Extend<K> for Counter<K, C> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for key in iter {
+            self.add(key);
+        }
+    }
+}
+
+impl<K, C: MapConstructor<K>> FromIterator<K> for Counter<K, C> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut counter = Self::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArrayMapConstructor;
+    #[cfg(feature = "std")]
+    use crate::BTreeMapConstructor;
+
+    #[test]
+    fn test_counter() {
+        fn foo<C: MapConstructor<char>>() {
+            let mut counter: Counter<char, C> = "abracadabra".chars().collect();
+            assert_eq!(counter.count(&'a'), 5);
+            assert_eq!(counter.count(&'z'), 0);
+            assert_eq!(counter.total(), 11);
+            assert_eq!(counter.len(), 5);
+
+            counter.add_n('z', 3);
+            assert!(counter.remove_one(&'z'));
+            assert_eq!(counter.remove_n(&'z', 5), 2);
+            assert!(!counter.remove_one(&'z'));
+            assert_eq!(counter.len(), 5);
+            assert_eq!(counter.total(), 11);
+
+            let other: Counter<char, C> = "bad".chars().collect();
+            counter.merge(&other);
+            assert_eq!(counter.count(&'b'), 3);
+            assert_eq!(counter.total(), 14);
+            counter.subtract(&other);
+            counter.subtract(&other);
+            counter.subtract(&other);
+            assert_eq!(counter.count(&'b'), 0);
+            assert_eq!(counter.count(&'d'), 0);
+            assert_eq!(counter.count(&'a'), 3);
+            assert_eq!(counter.len(), 3);
+            assert_eq!(counter.total(), counter.iter().map(|(_, n)| n).sum());
+
+            counter.clear();
+            assert!(counter.is_empty());
+        }
+
+        #[cfg(feature = "std")]
+        foo::<HashMapConstructor>();
+        #[cfg(feature = "std")]
+        foo::<BTreeMapConstructor>();
+        foo::<ArrayMapConstructor<6>>();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_counter_most_common() {
+        let counter: Counter<_> = "mississippi".chars().collect();
+        assert_eq!(counter.most_common(1)[0].1, 4); // 'i' and 's' are tied
+        let top = counter.most_common(3);
+        assert_eq!(top.iter().map(|(_, n)| *n).collect::<Vec<_>>(), [4, 4, 2]);
+        assert_eq!(top[2], (&'p', 2));
+        assert_eq!(counter.most_common(10).len(), 4);
+        assert!(counter.most_common(0).is_empty());
+    }
+}
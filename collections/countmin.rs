@@ -0,0 +1,158 @@
+use core::hash::{BuildHasher, Hash};
+use super::double_hashing;
+
+// `depth` rows of `width` counters stored row by row. Each row picks one counter with double hashing.
+// With width = e / epsilon and depth = ln(1 / delta), estimates exceed the true count by at most
+// epsilon * total with probability 1 - delta, and never underestimate.
+// `S` is the storage, either `Vec<u64>` or `[u64; N]` for no_std.
+
+#[cfg(feature = "std")]
+pub struct CountMinSketch<H: BuildHasher, S = Vec<u64>> {
+    build_hasher: H,
+    counters: S,
+    depth: usize,
+    total: u64,
+}
+
+#[cfg(not(feature = "std"))]
+pub struct CountMinSketch<H: BuildHasher, S> {
+    build_hasher: H,
+    counters: S,
+    depth: usize,
+    total: u64,
+}
+
+fn counter_indices(x: u64, depth: usize, width: usize) -> impl Iterator<Item = usize> {
+    double_hashing(x, depth as _, width).enumerate().map(move |(row, column)| row * width + column)
+}
+
+#[cfg(feature = "std")]
+impl<H: BuildHasher> CountMinSketch<H, Vec<u64>> {
+    pub fn new(build_hasher: H, width: usize, depth: usize) -> Self {
+        Self::with_storage(build_hasher, vec![0; width * depth], depth)
+    }
+
+    /// Chooses width = e / epsilon and depth = ln(1 / delta), so that estimates are within
+    /// `epsilon * total` of the true counts with probability `1 - delta`.
+    pub fn with_error(build_hasher: H, epsilon: f64, delta: f64) -> Self {
+        debug_assert!(epsilon > 0. && delta > 0. && delta < 1.);
+        let width = (core::f64::consts::E / epsilon).ceil() as usize;
+        let depth = (1. / delta).ln().ceil().max(1.) as usize;
+        Self::new(build_hasher, width, depth)
+    }
+}
+
+impl<H: BuildHasher, const N: usize> CountMinSketch<H, [u64; N]> {
+    /// Creates a sketch with `depth` rows of `N / depth` counters.
+    pub fn new(build_hasher: H, depth: usize) -> Self {
+        Self::with_storage(build_hasher, [0; N], depth)
+    }
+}
+
+impl<H: BuildHasher, S: AsRef<[u64]> + AsMut<[u64]>> CountMinSketch<H, S> {
+    /// `storage` is split into `depth` rows; its length must be a multiple of `depth`.
+    pub fn with_storage(build_hasher: H, storage: S, depth: usize) -> Self {
+        assert!(depth > 0 && !storage.as_ref().is_empty() && storage.as_ref().len() % depth == 0);
+        Self { build_hasher, counters: storage, depth, total: 0 }
+    }
+
+    pub fn width(&self) -> usize {
+        self.counters.as_ref().len() / self.depth
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The sum of all added counts.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn add(&mut self, key: impl Hash, n: u64) {
+        let x = self.build_hasher.hash_one(key);
+        for i in counter_indices(x, self.depth, self.width()) {
+            let counter = &mut self.counters.as_mut()[i];
+            *counter = counter.saturating_add(n);
+        }
+        self.total = self.total.saturating_add(n);
+    }
+
+    /// Adds with conservative update: only the counters below the new estimate are raised.
+    /// This reduces the overestimation, but the counts can no longer be decreased.
+    pub fn add_conservative(&mut self, key: impl Hash, n: u64) {
+        let x = self.build_hasher.hash_one(key);
+        let target = counter_indices(x, self.depth, self.width()).map(|i| self.counters.as_ref()[i]).min().unwrap_or(0).saturating_add(n);
+        for i in counter_indices(x, self.depth, self.width()) {
+            let counter = &mut self.counters.as_mut()[i];
+            *counter = (*counter).max(target);
+        }
+        self.total = self.total.saturating_add(n);
+    }
+
+    /// Returns an upper bound of the count of the key.
+    pub fn estimate(&self, key: impl Hash) -> u64 {
+        let x = self.build_hasher.hash_one(key);
+        counter_indices(x, self.depth, self.width()).map(|i| self.counters.as_ref()[i]).min().unwrap_or(0)
+    }
+
+    /// Adds the counts of another sketch. Both must have the same dimensions and hasher.
+    pub fn merge(&mut self, other: &Self) {
+        assert!(self.depth == other.depth && self.counters.as_ref().len() == other.counters.as_ref().len());
+        for (a, b) in self.counters.as_mut().iter_mut().zip(other.counters.as_ref()) {
+            *a = a.saturating_add(*b);
+        }
+        self.total = self.total.saturating_add(other.total);
+    }
+
+    pub fn clear(&mut self) {
+        self.counters.as_mut().iter_mut().for_each(|x| *x = 0);
+        self.total = 0;
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type DeterministicState = core::hash::BuildHasherDefault<std::hash::DefaultHasher>;
+
+    #[test]
+    fn test_count_min_sketch() {
+        let mut sketch = CountMinSketch::<_>::with_error(DeterministicState::default(), 0.001, 0.01);
+        assert_eq!((sketch.width(), sketch.depth()), (2719, 5));
+
+        for i in 0..10000u64 {
+            sketch.add(i, i % 10 + 1);
+        }
+        sketch.add("hot", 5000);
+        assert_eq!(sketch.total(), 55000 + 5000);
+        assert!((5000..=5000 + 60).contains(&sketch.estimate("hot")));
+        let within_bound = (0..10000u64).filter(|&i| sketch.estimate(i) <= i % 10 + 1 + 60).count();
+        assert!(within_bound >= 9900);
+        assert!((0..10000u64).all(|i| sketch.estimate(i) > i % 10));
+    }
+
+    #[test]
+    fn test_count_min_sketch_conservative_and_merge() {
+        let hasher = DeterministicState::default();
+        let mut plain = CountMinSketch::<_, [u64; 256]>::new(hasher.clone(), 4);
+        let mut conservative = CountMinSketch::<_, [u64; 256]>::new(hasher.clone(), 4);
+        for i in 0..1000 {
+            plain.add(i % 300, 1);
+            conservative.add_conservative(i % 300, 1);
+        }
+        let error = |sketch: &CountMinSketch<_, [u64; 256]>| (0..300).map(|i| sketch.estimate(i) - if i < 100 { 4 } else { 3 }).sum::<u64>();
+        assert!(error(&conservative) <= error(&plain));
+
+        let mut other = CountMinSketch::<_, [u64; 256]>::new(hasher, 4);
+        other.add("x", 7);
+        plain.merge(&other);
+        assert!(plain.estimate("x") >= 7);
+        assert_eq!(plain.total(), 1007);
+
+        plain.clear();
+        assert_eq!(plain.estimate("x"), 0);
+    }
+}
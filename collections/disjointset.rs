@@ -0,0 +1,188 @@
+use core::marker::PhantomData;
+
+/// Union-find over the ids `0..len`, typically index types created by `new_index_type!`.
+/// Uses union by size, and path compression unless created with `with_rollback`.
+/// In rollback mode every union is recorded so it can be undone with `rollback`, at the cost of O(log n) `find`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct DisjointSet<I: Into<usize> + From<usize> + Copy = usize> {
+    parent: Vec<usize>,
+    size: Vec<usize>, // only meaningful for roots
+    components: usize,
+    history: Option<Vec<(usize, usize)>>, // (child root, parent root) of each union in rollback mode
+    phantom: PhantomData<I>,
+}
+
+#[cfg(feature = "std")]
+impl<I: Into<usize> + From<usize> + Copy> DisjointSet<I> {
+    /// Creates `n` singleton sets.
+    pub fn new(n: usize) -> Self {
+        DisjointSet {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            components: n,
+            history: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates `n` singleton sets with unions recorded for `rollback`. Path compression is disabled.
+    pub fn with_rollback(n: usize) -> Self {
+        Self { history: Some(vec![]), ..Self::new(n) }
+    }
+
+    /// Adds a new singleton set and returns its id.
+    pub fn push(&mut self) -> I {
+        let id = self.parent.len();
+        self.parent.push(id);
+        self.size.push(1);
+        self.components += 1;
+        id.into()
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// The number of disjoint sets.
+    pub fn components_len(&self) -> usize {
+        self.components
+    }
+
+    fn root(&self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            x = self.parent[x];
+        }
+        x
+    }
+
+    fn find_index(&mut self, x: usize) -> usize {
+        let root = self.root(x);
+        if self.history.is_none() {
+            let mut x = x;
+            while self.parent[x] != root {
+                x = core::mem::replace(&mut self.parent[x], root);
+            }
+        }
+        root
+    }
+
+    /// Returns the representative of the set containing `x`.
+    pub fn find(&mut self, x: I) -> I {
+        self.find_index(x.into()).into()
+    }
+
+    /// Merges the sets containing `a` and `b`. Returns false if they were already in the same set.
+    pub fn union(&mut self, a: I, b: I) -> bool {
+        let (mut a, mut b) = (self.find_index(a.into()), self.find_index(b.into()));
+        if a == b {
+            return false
+        }
+        if self.size[a] < self.size[b] {
+            core::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.components -= 1;
+        if let Some(history) = &mut self.history {
+            history.push((b, a));
+        }
+        true
+    }
+
+    pub fn same(&mut self, a: I, b: I) -> bool {
+        self.find_index(a.into()) == self.find_index(b.into())
+    }
+
+    /// The number of elements in the set containing `x`.
+    pub fn set_size(&mut self, x: I) -> usize {
+        let root = self.find_index(x.into());
+        self.size[root]
+    }
+
+    /// Iterates the sets, each as a list of its elements in ascending order.
+    pub fn components(&self) -> impl Iterator<Item = Vec<I>> {
+        let mut groups: Vec<Vec<I>> = vec![vec![]; self.len()];
+        for x in 0..self.len() {
+            groups[self.root(x)].push(x.into());
+        }
+        groups.into_iter().filter(|group| !group.is_empty())
+    }
+
+    /// Returns a checkpoint that can be passed to `rollback`. Panics if not in rollback mode.
+    pub fn checkpoint(&self) -> usize {
+        self.history.as_ref().expect("DisjointSet is not created with rollback").len()
+    }
+
+    /// Undoes the unions made after the checkpoint. Panics if not in rollback mode.
+    pub fn rollback(&mut self, checkpoint: usize) {
+        while self.checkpoint() > checkpoint {
+            self.undo();
+        }
+    }
+
+    /// Undoes the last union. Returns false if there is nothing to undo. Panics if not in rollback mode.
+    pub fn undo(&mut self) -> bool {
+        let history = self.history.as_mut().expect("DisjointSet is not created with rollback");
+        let Some((child, parent)) = history.pop() else { return false };
+        self.parent[child] = child;
+        self.size[parent] -= self.size[child];
+        self.components += 1;
+        true
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::new_index_type;
+
+    #[test]
+    fn test_disjoint_set() {
+        new_index_type!(Node);
+
+        let mut sets: DisjointSet<Node> = DisjointSet::new(6);
+        assert!(sets.union(Node(0), Node(1)));
+        assert!(sets.union(Node(2), Node(3)));
+        assert!(sets.union(Node(1), Node(3)));
+        assert!(!sets.union(Node(0), Node(2)));
+        assert!(sets.same(Node(0), Node(3)));
+        assert!(!sets.same(Node(0), Node(4)));
+        assert_eq!(sets.set_size(Node(2)), 4);
+        assert_eq!(sets.components_len(), 3);
+
+        let node = sets.push();
+        assert_eq!(node, Node(6));
+        sets.union(node, Node(5));
+        let components: Vec<_> = sets.components().collect();
+        assert_eq!(components, [vec![Node(0), Node(1), Node(2), Node(3)], vec![Node(4)], vec![Node(5), Node(6)]]);
+    }
+
+    #[test]
+    fn test_disjoint_set_rollback() {
+        let mut sets: DisjointSet = DisjointSet::with_rollback(5);
+        sets.union(0, 1);
+        let checkpoint = sets.checkpoint();
+        sets.union(2, 3);
+        sets.union(1, 3);
+        sets.union(0, 2); // no-op, not recorded
+        assert_eq!(sets.set_size(0), 4);
+        assert_eq!(sets.components_len(), 2);
+
+        sets.rollback(checkpoint);
+        assert!(sets.same(0, 1));
+        assert!(!sets.same(1, 2) && !sets.same(2, 3));
+        assert_eq!(sets.set_size(1), 2);
+        assert_eq!(sets.components_len(), 4);
+
+        assert!(sets.undo());
+        assert!(!sets.undo());
+        assert_eq!(sets.components().count(), 5);
+    }
+}
@@ -1,14 +1,93 @@
-use core::hash::{BuildHasher, Hash, Hasher};
+use core::hash::{BuildHasher, Hash};
 
-// we use 30 bits of the hash output
-// 14 bits are used for indexing 16384 registers, providing 1.04/sqrt(16384) = 0.8125% standard errors
-// 15 bits are used for estimate the numbers in a 4 bit register
-// todo: 15 bits are a bit insufficient, but more bits makes packing more challenging. Need a bitvec helper.
-// todo: making them configurable after generic const expr stablized?
+// the 64-bit hash is split into the top `p` bits indexing 2^p registers, providing 1.04/sqrt(2^p) standard errors,
+// and the remaining 64 - p bits whose number of leading zeros (plus one) is stored in a 6 bit register.
+// registers are packed into bytes with `register_get` and `register_set`, 4 registers per 3 bytes.
+// `S` is the storage, either `Vec<u8>` or `[u8; N]` for no_std, where N >= hyperloglog_storage_size(p).
+// a `Vec<u8>` sketch starts sparse: the nonzero registers are kept as a sorted list of 3 byte (index << 6 | rho) entries
+// in the same storage, converted to the dense registers once the list grows past 2^p / 8 entries (half the dense size).
+// both representations hold the same registers, so `count` and `merge` give the same results regardless of the mode.
+// the cardinality is estimated with the improved estimator from Otmar Ertl, "New cardinality estimation algorithms
+// for HyperLogLog sketches" (2017), which is unbiased over the full range without HLL++'s empirical bias tables
+// or the switch to linear counting, and needs neither `ln` nor any other std-only floating point function.
 
-pub struct HyperLogLog<H: BuildHasher> {
+// serialized format: b"HLL", version, precision, followed by
+// - version 1 (read only): precision 14 and 8192 bytes of 4 bit registers, two per byte with the even index in the high
+//   nibble, indexed by bits 15..29 of the hash. they are widened to 6 bits; as the index bits differ, such a sketch
+//   keeps its count but must not be merged with newer sketches or receive the items it has already seen.
+// - version 2 (dense): the hyperloglog_storage_size(precision) bytes of packed registers.
+// - version 3 (sparse): the number of entries as a little endian u32, and the 3 byte entries.
+// sketches can only be merged or restored with the same hasher; use a deterministic one (not `RandomState`) across processes.
+
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct HyperLogLog<H: BuildHasher, S = Vec<u8>> {
+    build_hasher: H,
+    precision: u8,
+    registers: S,
+    sparse: bool,
+}
+
+#[cfg(not(feature = "std"))]
+#[derive(Clone)]
+pub struct HyperLogLog<H: BuildHasher, S = [u8; hyperloglog_storage_size(14)]> {
     build_hasher: H,
-    registers: [u8; 8192] // 2 registers are packed into a u8
+    precision: u8,
+    registers: S,
+    sparse: bool,
+}
+
+/// the number of bytes needed for the registers of the given precision
+pub const fn hyperloglog_storage_size(precision: u8) -> usize {
+    (6 << precision) / 8
+}
+
+/// Register storage of a `HyperLogLog`, `Vec<u8>` or `[u8; N]`. Only resizable storage can use the sparse representation.
+pub trait HyperLogLogStorage: AsRef<[u8]> + AsMut<[u8]> {
+    /// Resizes to `len` bytes, filling with zeros.
+    fn resize(&mut self, len: usize);
+}
+
+#[cfg(feature = "std")]
+impl HyperLogLogStorage for Vec<u8> {
+    fn resize(&mut self, len: usize) {
+        let shrink = len < self.len();
+        Vec::resize(self, len, 0);
+        if shrink {
+            self.shrink_to_fit();
+        }
+    }
+}
+
+impl<const N: usize> HyperLogLogStorage for [u8; N] {
+    fn resize(&mut self, _: usize) {
+        unreachable!("fixed storage is always dense")
+    }
+}
+
+fn sparse_decode(entry: &[u8; 3]) -> (usize, u8) {
+    let x = u32::from_be_bytes([0, entry[0], entry[1], entry[2]]);
+    ((x >> 6) as usize, (x & 0b0011_1111) as u8)
+}
+
+fn sparse_encode(j: usize, rho: u8) -> [u8; 3] {
+    let [_, a, b, c] = ((j as u32) << 6 | rho as u32).to_be_bytes();
+    [a, b, c]
+}
+
+fn register_get(registers: &[u8], j: usize) -> u8 {
+    let (i, shift) = (j * 6 / 8, j * 6 % 8);
+    let window = registers[i] as u16 | (registers.get(i + 1).copied().unwrap_or(0) as u16) << 8;
+    (window >> shift) as u8 & 0b0011_1111
+}
+
+fn register_set(registers: &mut [u8], j: usize, value: u8) {
+    let (i, shift) = (j * 6 / 8, j * 6 % 8);
+    let (mask, value) = (0b0011_1111u16 << shift, (value as u16) << shift);
+    registers[i] = (registers[i] & !mask as u8) | value as u8;
+    if shift > 2 {
+        registers[i + 1] = (registers[i + 1] & !(mask >> 8) as u8) | (value >> 8) as u8;
+    }
 }
 
 #[cfg(feature = "std")]
@@ -18,58 +97,278 @@ impl Default for HyperLogLog<std::hash::RandomState> {
     }
 }
 
-impl<H: BuildHasher> HyperLogLog<H> {
+#[cfg(feature = "std")]
+impl<H: BuildHasher> HyperLogLog<H, Vec<u8>> {
+    /// Creates a sketch with precision 14, i.e. 16384 registers and 0.81% standard error. It starts sparse and grows up to 12 KiB.
     pub fn new(build_hasher: H) -> Self {
-        Self {
-            build_hasher,
-            registers: [0; 8192]
+        Self::with_precision(build_hasher, 14)
+    }
+
+    /// `precision` must be in `4..=18`. The sketch starts sparse.
+    pub fn with_precision(build_hasher: H, precision: u8) -> Self {
+        assert!((4..=18).contains(&precision));
+        Self { build_hasher, precision, registers: vec![], sparse: true }
+    }
+
+    /// Creates a sketch of the union of the given sketches, which must be built with `build_hasher` and have the same precision.
+    pub fn union<'a>(build_hasher: H, sketches: impl IntoIterator<Item = &'a Self>) -> Self where H: 'a {
+        let mut sketches = sketches.into_iter().peekable();
+        let mut result = Self::with_precision(build_hasher, sketches.peek().map_or(14, |sketch| sketch.precision));
+        for sketch in sketches {
+            result.merge(sketch);
         }
+        result
+    }
+
+    /// Reads a sketch written by `write_to`. `build_hasher` must be the same as the one used to build the sketch.
+    pub fn read_from(build_hasher: H, reader: &mut impl std::io::Read) -> std::io::Result<Self> {
+        use crate::ReadExt;
+
+        let invalid = |message| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+
+        let header = reader.read_exact_alloc(5)?;
+        let precision = header[4];
+        if header[..3] != *b"HLL" || !(1..=3).contains(&header[3]) || !(4..=18).contains(&precision) || header[3] == 1 && precision != 14 {
+            return Err(invalid("unsupported HyperLogLog header"))
+        }
+
+        if header[3] == 1 {
+            let mut sketch = Self::with_storage(build_hasher, 14, vec![0; hyperloglog_storage_size(14)]);
+            for (i, byte) in reader.read_exact_alloc(8192)?.into_iter().enumerate() {
+                register_set(&mut sketch.registers, 2 * i, byte >> 4);
+                register_set(&mut sketch.registers, 2 * i + 1, byte & 0b1111);
+            }
+            return Ok(sketch)
+        }
+
+        if header[3] == 2 {
+            let mut sketch = Self::with_storage(build_hasher, precision, vec![0; hyperloglog_storage_size(precision)]);
+            reader.read_exact(&mut sketch.registers)?;
+            return Ok(sketch)
+        }
+
+        let mut len = [0; 4];
+        reader.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len) as usize;
+        if len > 1 << precision {
+            return Err(invalid("too many HyperLogLog entries"))
+        }
+
+        let mut sketch = Self::with_precision(build_hasher, precision);
+        for entry in reader.read_exact_alloc(len * 3)?.as_chunks().0 {
+            let (j, rho) = sparse_decode(entry);
+            if j >= 1 << precision || rho == 0 || rho > 65 - precision {
+                return Err(invalid("invalid HyperLogLog entry"))
+            }
+            sketch.update(j, rho);
+        }
+        Ok(sketch)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<H: BuildHasher> HyperLogLog<H> {
+    /// Creates a dense sketch with precision 14, i.e. 16384 registers and 0.81% standard error, in 12 KiB.
+    pub fn new(build_hasher: H) -> Self {
+        Self::with_storage(build_hasher, 14, [0; hyperloglog_storage_size(14)])
+    }
+}
+
+impl<H: BuildHasher, S: HyperLogLogStorage> HyperLogLog<H, S> {
+    /// Creates a dense sketch. `precision` must be in `4..=18` and `storage` must hold at least `hyperloglog_storage_size(precision)` bytes.
+    pub fn with_storage(build_hasher: H, precision: u8, mut storage: S) -> Self {
+        assert!((4..=18).contains(&precision) && storage.as_ref().len() >= hyperloglog_storage_size(precision));
+        storage.as_mut().iter_mut().for_each(|x| *x = 0);
+        Self { build_hasher, precision, registers: storage, sparse: false }
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        self.sparse
+    }
+
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    fn num_registers(&self) -> usize {
+        1 << self.precision
     }
 
     pub fn insert(&mut self, v: impl Hash) {
-        let x = {
-            let mut hasher = self.build_hasher.build_hasher();
-            v.hash(&mut hasher);
-            hasher.finish()
-        };
+        let x = self.build_hasher.hash_one(v);
 
-        let j = (x >> 15) & 0b0011_1111_1111_1111;
-        let w = x as u16 & 0b0111_1111_1111_1111; // 15 bits
-        let rho = w.leading_zeros() as u8; // at least one for more accurate linear counting
+        let j = (x >> (64 - self.precision)) as usize;
+        let w = (x << self.precision) | (1 << (self.precision - 1)); // the sentinel bit caps rho at 64 - p + 1
+        let rho = w.leading_zeros() as u8 + 1;
+        self.update(j, rho);
+    }
 
-        let register = &mut self.registers[(j >> 1) as usize];
-        let shift = if j & 1 == 1 { 0 } else { 4 };
-        let mask = 0b1111 << shift;
-        let old = (*register & mask) >> shift;
-        let new = old.max(rho);
-        *register = (*register & !mask) | (new << shift);
+    fn sparse_entries(&self) -> &[[u8; 3]] {
+        self.registers.as_ref().as_chunks().0
+    }
+
+    // raises register `j` to `rho`, converting to the dense representation if the sparse list is full
+    fn update(&mut self, j: usize, rho: u8) {
+        if !self.sparse {
+            if rho > register_get(self.registers.as_ref(), j) {
+                register_set(self.registers.as_mut(), j, rho);
+            }
+            return
+        }
+
+        match self.sparse_entries().binary_search_by_key(&j, |entry| sparse_decode(entry).0) {
+            Ok(i) if rho > sparse_decode(&self.sparse_entries()[i]).1 => {
+                self.registers.as_mut()[i * 3..i * 3 + 3].copy_from_slice(&sparse_encode(j, rho));
+            }
+            Ok(_) => {}
+            Err(i) if self.sparse_entries().len() < self.num_registers() / 8 => {
+                let len = self.registers.as_ref().len();
+                self.registers.resize(len + 3);
+                let registers = self.registers.as_mut();
+                registers.copy_within(i * 3..len, i * 3 + 3);
+                registers[i * 3..i * 3 + 3].copy_from_slice(&sparse_encode(j, rho));
+            }
+            Err(_) => {
+                self.densify();
+                register_set(self.registers.as_mut(), j, rho);
+            }
+        }
+    }
+
+    // the dense registers are built after the sparse entries and then moved to the front, avoiding a temporary buffer
+    fn densify(&mut self) {
+        let sparse_len = self.registers.as_ref().len();
+        let dense_len = hyperloglog_storage_size(self.precision);
+        self.registers.resize(sparse_len + dense_len);
+
+        let (entries, dense) = self.registers.as_mut().split_at_mut(sparse_len);
+        for entry in entries.as_chunks().0 {
+            let (j, rho) = sparse_decode(entry);
+            register_set(dense, j, rho);
+        }
+
+        self.registers.as_mut().copy_within(sparse_len.., 0);
+        self.registers.resize(dense_len);
+        self.sparse = false;
+    }
+
+    /// Merges another sketch (built with the same hasher and precision) into this one. The result counts the union of both streams.
+    /// Merging a dense sketch into a sparse one makes it dense.
+    pub fn merge<S2: HyperLogLogStorage>(&mut self, other: &HyperLogLog<H, S2>) {
+        assert_eq!(self.precision, other.precision);
+        if other.sparse {
+            for entry in other.sparse_entries() {
+                let (j, rho) = sparse_decode(entry);
+                self.update(j, rho);
+            }
+        } else {
+            if self.sparse {
+                self.densify();
+            }
+            for j in 0..self.num_registers() {
+                let r = register_get(other.registers.as_ref(), j);
+                if r > register_get(self.registers.as_ref(), j) {
+                    register_set(self.registers.as_mut(), j, r);
+                }
+            }
+        }
     }
 
     pub fn count(&self) -> usize {
-        let alpha = 0.7213 / (1.0 + 1.079 / 16384.);
+        let mut histogram = [0u32; 64];
+        if self.sparse {
+            histogram[0] = (self.num_registers() - self.sparse_entries().len()) as u32;
+            for entry in self.sparse_entries() {
+                histogram[sparse_decode(entry).1 as usize] += 1;
+            }
+        } else {
+            for j in 0..self.num_registers() {
+                histogram[register_get(self.registers.as_ref(), j) as usize] += 1;
+            }
+        }
 
-        let z = (0..16384).map(|j| {
-            let register = &self.registers[(j >> 1) as usize];
-            let shift = if j & 1 == 1 { 0 } else { 4 };
-            let mask = 0b1111 << shift;
-            let r = (*register & mask) >> shift;
-            1. / 2i32.pow(r as _) as f32
-        }).sum::<f32>();
+        let q = 64 - self.precision as usize;
+        let m = self.num_registers() as f64;
+        let mut z = m * tau(1. - histogram[q + 1] as f64 / m);
+        for k in (1..=q).rev() {
+            z = 0.5 * (z + histogram[k] as f64);
+        }
+        z += m * sigma(histogram[0] as f64 / m);
 
-        let mut raw = alpha * 16384. * 16384. / z;
+        (m * m / (2. * core::f64::consts::LN_2 * z) + 0.5) as _
+    }
 
-        #[cfg(feature = "std")]
-        if raw <= 2.5 * 16384. { // linear counting for small cardinality.
-            let zeros = self.registers.iter().map(|&r| {
-                (r & 0b0000_1111 == 0) as i32 + (r & 0b1111_0000 == 0) as i32
-            }).sum::<i32>();
-            raw = 16384. * (16384. / zeros as f32).ln()
+    #[cfg(feature = "std")]
+    pub fn write_to(&self, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+        writer.write_all(b"HLL")?;
+        if self.sparse {
+            writer.write_all(&[3, self.precision])?;
+            writer.write_all(&(self.sparse_entries().len() as u32).to_le_bytes())?;
+            writer.write_all(self.registers.as_ref())
+        } else {
+            writer.write_all(&[2, self.precision])?;
+            writer.write_all(&self.registers.as_ref()[..hyperloglog_storage_size(self.precision)])
         }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(9 + self.registers.as_ref().len());
+        self.write_to(&mut buf).unwrap();
+        buf
+    }
+}
 
-        raw as _
+// x + sum_{k >= 1} x^(2^k) 2^(k-1), accounting for the registers that are still zero
+fn sigma(mut x: f64) -> f64 {
+    if x == 1. {
+        return f64::INFINITY
+    }
+
+    let (mut y, mut z) = (1., x);
+    loop {
+        x *= x;
+        let z_prev = z;
+        z += x * y;
+        y += y;
+        if z == z_prev {
+            return z
+        }
     }
 }
 
+// (1 - x - sum_{k >= 1} (1 - x^(2^-k))^2 2^-k) / 3, accounting for the registers that are saturated
+fn tau(mut x: f64) -> f64 {
+    if x == 0. || x == 1. {
+        return 0.
+    }
+
+    let (mut y, mut z) = (1., 1. - x);
+    loop {
+        x = sqrt(x);
+        let z_prev = z;
+        y *= 0.5;
+        z -= (1. - x) * (1. - x) * y;
+        if z == z_prev {
+            return z / 3.
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn sqrt(x: f64) -> f64 {
+    x.sqrt()
+}
+
+#[cfg(not(feature = "std"))]
+fn sqrt(x: f64) -> f64 { // Newton's method from the halved exponent
+    let mut r = f64::from_bits((x.to_bits() >> 1) + (0x3ff << 51));
+    for _ in 0..6 {
+        r = 0.5 * (r + x / r);
+    }
+    r
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,4 +399,154 @@ mod tests {
         }
         assert!((hll.count() as i64 - 100000).abs() < 2438);
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_hyperloglog_precisions() {
+        for precision in [4, 8, 12, 18] {
+            let mut hll = HyperLogLog::with_precision(core::hash::BuildHasherDefault::<std::hash::DefaultHasher>::default(), precision);
+            let tolerance = 4. * 1.04 / ((1 << precision) as f64).sqrt(); // 4 standard errors
+            for n in [10, 100, 10000, 200000] {
+                (0..n).for_each(|i| hll.insert(i));
+                assert!((hll.count() as f64 / n as f64 - 1.).abs() < tolerance.max(0.05), "precision {precision}, n {n}");
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_hyperloglog_merge_and_serialize() {
+        type DeterministicState = core::hash::BuildHasherDefault<std::hash::DefaultHasher>;
+
+        let mut shards: Vec<_> = (0..4).map(|_| HyperLogLog::new(DeterministicState::default())).collect();
+        for i in 0..40000 {
+            shards[i % 4].insert(i / 2); // each value goes to two shards
+        }
+        let union = HyperLogLog::union(DeterministicState::default(), &shards);
+        assert!((union.count() as i64 - 20000).abs() < 488);
+
+        let bytes = shards[0].to_bytes();
+        assert_eq!(bytes.len(), 12293);
+        let mut restored = HyperLogLog::read_from(DeterministicState::default(), &mut &bytes[..]).unwrap();
+        assert_eq!(restored.count(), shards[0].count());
+        for shard in &shards[1..] {
+            let mut buf = vec![];
+            shard.write_to(&mut buf).unwrap();
+            restored.merge(&HyperLogLog::read_from(DeterministicState::default(), &mut &buf[..]).unwrap());
+        }
+        assert_eq!(restored.count(), union.count());
+
+        assert!(HyperLogLog::read_from(DeterministicState::default(), &mut &bytes[1..]).is_err());
+        assert!(HyperLogLog::read_from(DeterministicState::default(), &mut &bytes[..100]).is_err());
+
+        // version 1: 4 bit registers with precision 14, registers 0 and 1 in the first byte and 201 in the 101st
+        let mut v1 = b"HLL\x01\x0e".to_vec();
+        v1.extend([0u8; 8192]);
+        (v1[5], v1[105]) = (0x31, 0x02);
+        let sketch = HyperLogLog::read_from(DeterministicState::default(), &mut &v1[..]).unwrap();
+        assert_eq!((sketch.precision(), sketch.count()), (14, 3));
+        let registers: Vec<_> = (0..1 << 14).map(|j| register_get(&sketch.registers, j)).filter(|&r| r > 0).collect();
+        assert_eq!(registers, [3, 1, 2]);
+        assert_eq!(register_get(&sketch.registers, 201), 2);
+        let restored = HyperLogLog::read_from(DeterministicState::default(), &mut &sketch.to_bytes()[..]).unwrap();
+        assert_eq!(restored.registers, sketch.registers);
+        v1[4] = 12;
+        assert!(HyperLogLog::read_from(DeterministicState::default(), &mut &v1[..]).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_hyperloglog_sparse() {
+        type DeterministicState = core::hash::BuildHasherDefault<std::hash::DefaultHasher>;
+        let new_dense = || HyperLogLog::with_storage(DeterministicState::default(), 14, vec![0; hyperloglog_storage_size(14)]);
+
+        let (mut sparse, mut dense) = (HyperLogLog::new(DeterministicState::default()), new_dense());
+        for i in 0..3 {
+            sparse.insert(i);
+            dense.insert(i);
+        }
+        assert!(sparse.is_sparse() && !dense.is_sparse());
+        assert_eq!(sparse.count(), 3);
+        assert_eq!(sparse.to_bytes().len(), 9 + 3 * 3);
+        assert_eq!(HyperLogLog::read_from(DeterministicState::default(), &mut &sparse.to_bytes()[..]).unwrap().count(), 3);
+
+        for i in 0..1000 {
+            sparse.insert(i);
+            dense.insert(i);
+        }
+        assert!(sparse.is_sparse());
+        assert_eq!(sparse.count(), dense.count());
+
+        let mut other = HyperLogLog::new(DeterministicState::default());
+        (500..2500).for_each(|i| other.insert(i));
+        let mut merged = dense.clone();
+        merged.merge(&other);
+        other.merge(&sparse); // sparse into sparse, past the threshold
+        assert!(!other.is_sparse());
+        assert_eq!(other.count(), merged.count());
+        assert!((other.count() as i64 - 2500).abs() < 80);
+
+        for i in 0..3000 {
+            sparse.insert(i);
+            dense.insert(i);
+        }
+        assert!(!sparse.is_sparse());
+        assert_eq!(sparse.to_bytes(), dense.to_bytes());
+
+        let mut small = HyperLogLog::new(DeterministicState::default());
+        small.insert("x");
+        small.merge(&new_dense());
+        assert!(!small.is_sparse());
+        assert_eq!(small.count(), 1);
+    }
+
+    #[test]
+    fn test_hyperloglog_fixed_storage() {
+        #[derive(Default)]
+        struct SplitMix(u64);
+        impl core::hash::Hasher for SplitMix {
+            fn write(&mut self, bytes: &[u8]) {
+                for &b in bytes {
+                    self.0 = (self.0 ^ b as u64).wrapping_mul(0x100000001b3);
+                }
+            }
+            fn finish(&self) -> u64 {
+                let mut z = self.0.wrapping_add(0x9e3779b97f4a7c15);
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+                z ^ (z >> 31)
+            }
+        }
+
+        type State = core::hash::BuildHasherDefault<SplitMix>;
+        let mut hll = HyperLogLog::<State, [u8; hyperloglog_storage_size(10)]>::with_storage(Default::default(), 10, [0; 768]);
+        for i in 0..3u32 {
+            hll.insert(i);
+        }
+        assert_eq!(hll.count(), 3);
+        for i in 0..50000u32 {
+            hll.insert(i);
+        }
+        assert!((hll.count() as i64 - 50000).abs() < 50000 * 4 * 104 / 3200);
+
+        #[cfg(not(feature = "std"))]
+        {
+            let mut hll: HyperLogLog<State> = HyperLogLog::new(Default::default());
+            (0..50000u32).for_each(|i| hll.insert(i));
+            assert!((hll.count() as i64 - 50000).abs() < 50000 * 4 * 104 / 12800);
+        }
+    }
+
+    #[test]
+    fn test_register_packing() {
+        let mut registers = [0u8; 12];
+        for j in 0..16 {
+            register_set(&mut registers, j, (j * 4 + 3) as u8 % 64);
+        }
+        register_set(&mut registers, 5, 63);
+        register_set(&mut registers, 5, 1);
+        for j in 0..16 {
+            assert_eq!(register_get(&registers, j), if j == 5 { 1 } else { (j * 4 + 3) as u8 % 64 });
+        }
+    }
 }
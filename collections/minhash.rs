@@ -0,0 +1,170 @@
+use core::hash::{BuildHasher, Hash};
+
+// one permutation hashing (Li, Owen and Zhang, 2012): a single 64-bit hash picks one of the k bins with its upper bits,
+// and each bin keeps the minimum hash it has seen. Empty bins are filled at query time with optimal densification
+// (Shrivastava, 2017): bin i borrows the value of the first nonempty bin in a sequence derived from i only,
+// so sketches of similar sets borrow from the same bins. Merging works on the raw bins, before densification.
+// `S` is the storage, either `Vec<u64>` or `[u64; N]` for no_std. Sketches are comparable only with the same hasher and k.
+
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct MinHash<H: BuildHasher, S = Vec<u64>> {
+    build_hasher: H,
+    bins: S,
+}
+
+#[cfg(not(feature = "std"))]
+#[derive(Clone)]
+pub struct MinHash<H: BuildHasher, S> {
+    build_hasher: H,
+    bins: S,
+}
+
+const EMPTY: u64 = u64::MAX;
+
+// splitmix64 finalizer, used to derive the densification sequences and the band hashes
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(feature = "std")]
+impl<H: BuildHasher> MinHash<H, Vec<u64>> {
+    /// Creates a sketch with `k` bins. The standard error of `jaccard` is about sqrt(J (1 - J) / k).
+    pub fn new(build_hasher: H, k: usize) -> Self {
+        Self::with_storage(build_hasher, vec![0; k])
+    }
+}
+
+impl<H: BuildHasher, const N: usize> MinHash<H, [u64; N]> {
+    /// Creates a sketch with `N` bins.
+    pub fn new(build_hasher: H) -> Self {
+        Self::with_storage(build_hasher, [0; N])
+    }
+}
+
+impl<H: BuildHasher, S: AsRef<[u64]> + AsMut<[u64]>> MinHash<H, S> {
+    /// Creates a sketch with one bin per element of `storage`.
+    pub fn with_storage(build_hasher: H, mut storage: S) -> Self {
+        assert!(!storage.as_ref().is_empty());
+        storage.as_mut().iter_mut().for_each(|x| *x = EMPTY);
+        Self { build_hasher, bins: storage }
+    }
+
+    /// The number of bins, k.
+    pub fn num_bins(&self) -> usize {
+        self.bins.as_ref().len()
+    }
+
+    /// Returns true if nothing has been inserted.
+    pub fn is_empty(&self) -> bool {
+        self.bins.as_ref().iter().all(|&x| x == EMPTY)
+    }
+
+    pub fn insert(&mut self, v: impl Hash) {
+        let x = self.build_hasher.hash_one(v).min(EMPTY - 1);
+        let bin = ((x as u128 * self.num_bins() as u128) >> 64) as usize;
+        let min = &mut self.bins.as_mut()[bin];
+        *min = (*min).min(x);
+    }
+
+    /// Merges another sketch into this one. The result is the sketch of the union of both sets.
+    pub fn merge(&mut self, other: &Self) {
+        assert_eq!(self.num_bins(), other.num_bins());
+        for (a, &b) in self.bins.as_mut().iter_mut().zip(other.bins.as_ref()) {
+            *a = (*a).min(b);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.bins.as_mut().iter_mut().for_each(|x| *x = EMPTY);
+    }
+
+    /// The densified signature, k values. All values are `u64::MAX` if the sketch is empty.
+    pub fn signature(&self) -> impl Iterator<Item = u64> + '_ {
+        let (bins, k) = (self.bins.as_ref(), self.num_bins() as u64);
+        let empty = self.is_empty();
+        (0..k).map(move |i| {
+            if empty || bins[i as usize] != EMPTY {
+                return bins[i as usize]
+            }
+            (0..).map(|attempt| bins[(mix(i << 32 | attempt) % k) as usize]).find(|&x| x != EMPTY).unwrap()
+        })
+    }
+
+    /// Estimates the Jaccard similarity |A ∩ B| / |A ∪ B| as the fraction of equal signature values.
+    pub fn jaccard(&self, other: &Self) -> f64 {
+        assert_eq!(self.num_bins(), other.num_bins());
+        let equal = self.signature().zip(other.signature()).filter(|(a, b)| a == b).count();
+        equal as f64 / self.num_bins() as f64
+    }
+
+    /// Splits the signature into bands of `rows` values and hashes each band, for locality-sensitive hashing.
+    /// Sketches sharing the hash of any band (at the same position) are candidate pairs: with b = k / rows bands,
+    /// a pair with similarity J becomes a candidate with probability 1 - (1 - J^rows)^b.
+    /// Typically the `(band index, band hash)` pairs are the keys of a `MultiMap` of the indexed documents.
+    pub fn bands(&self, rows: usize) -> impl Iterator<Item = u64> + '_ {
+        assert!(rows > 0 && self.num_bins().is_multiple_of(rows));
+        let mut signature = self.signature();
+        (0..self.num_bins() / rows).map(move |_| {
+            signature.by_ref().take(rows).fold(0u64, |h, x| mix(h ^ x).wrapping_add(x))
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type DeterministicState = core::hash::BuildHasherDefault<std::hash::DefaultHasher>;
+
+    #[test]
+    fn test_minhash_jaccard() {
+        let hasher = DeterministicState::default();
+        let (mut a, mut b) = (MinHash::<_>::new(hasher.clone(), 512), MinHash::<_>::new(hasher.clone(), 512));
+        assert!(a.is_empty());
+        (0..1500).for_each(|i| a.insert(i));
+        (500..2000).for_each(|i| b.insert(i));
+        assert!((a.jaccard(&b) - 0.5).abs() < 0.1);
+        assert_eq!(a.jaccard(&a), 1.);
+
+        let mut c = MinHash::<_>::new(hasher.clone(), 512);
+        (10000..11000).for_each(|i| c.insert(i));
+        assert!(a.jaccard(&c) < 0.05);
+
+        // densification: few items in many bins
+        let (mut x, mut y) = (MinHash::<_>::new(hasher.clone(), 512), MinHash::<_>::new(hasher, 512));
+        (0..20).for_each(|i| x.insert(i));
+        (0..19).for_each(|i| y.insert(i));
+        assert!(x.signature().all(|v| v != u64::MAX));
+        assert!(x.jaccard(&y) > 0.8);
+    }
+
+    #[test]
+    fn test_minhash_merge_and_bands() {
+        let hasher = DeterministicState::default();
+        let mut a = MinHash::<_, [u64; 128]>::new(hasher.clone());
+        let mut b = MinHash::<_, [u64; 128]>::new(hasher.clone());
+        let mut whole = MinHash::<_, [u64; 128]>::new(hasher.clone());
+        (0..1000).for_each(|i| a.insert(i));
+        (1000..2000).for_each(|i| b.insert(i));
+        (0..2000).for_each(|i| whole.insert(i));
+        a.merge(&b);
+        assert!(a.signature().eq(whole.signature()));
+
+        let shared_bands = |x: &MinHash<_, [u64; 128]>, y: &MinHash<_, [u64; 128]>| x.bands(4).zip(y.bands(4)).filter(|(p, q)| p == q).count();
+        assert_eq!(a.bands(4).count(), 32);
+        assert_eq!(shared_bands(&a, &whole), 32);
+        (0..2000).for_each(|i| b.insert(i));
+        assert!(shared_bands(&a, &b) > 0);
+
+        let mut other = MinHash::<_, [u64; 128]>::new(hasher);
+        (5000..7000).for_each(|i| other.insert(i));
+        assert_eq!(shared_bands(&a, &other), 0);
+
+        a.clear();
+        assert!(a.is_empty() && a.signature().all(|v| v == u64::MAX));
+    }
+}
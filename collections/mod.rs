@@ -1,11 +1,43 @@
-use core::{hash::{Hash, BuildHasher}, mem::{MaybeUninit, ManuallyDrop}, ops::{Deref, DerefMut}, iter::FusedIterator};
+use core::{hash::{Hash, BuildHasher}, mem::{MaybeUninit, ManuallyDrop}, ops::{Deref, DerefMut}, iter::FusedIterator, borrow::Borrow};
 #[cfg(feature = "std")]
-use std::collections::{HashMap, BTreeMap};
+use std::collections::{HashMap, BTreeMap, hash_map, btree_map};
 
 mod hyperloglog;
 pub use hyperloglog::*;
 
-pub trait Map<K, V>: Default + FromIterator<(K, V)> + IntoIterator<Item = (K, V)> {
+mod bloomfilter;
+pub use bloomfilter::*;
+
+mod countmin;
+pub use countmin::*;
+
+mod minhash;
+pub use minhash::*;
+
+mod counter;
+pub use counter::*;
+
+mod topk;
+pub use topk::*;
+
+mod bimap;
+pub use bimap::*;
+
+mod multimap;
+pub use multimap::*;
+
+mod disjointset;
+pub use disjointset::*;
+
+/// derives `k` indices in `0..m` from a single 64-bit hash: g_i = h1 + i * h2 (mod m),
+/// where h1 and h2 are the lower and upper 32 bits (Kirsch and Mitzenmacher, 2006).
+/// h2 is made odd so that the probes never collapse to a single index.
+fn double_hashing(x: u64, k: u32, m: usize) -> impl Iterator<Item = usize> {
+    let (h1, h2) = (x & 0xffff_ffff, x >> 32 | 1);
+    (0..k as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % m as u64) as usize)
+}
+
+pub trait Map<K, V>: Default + FromIterator<(K, V)> + IntoIterator<Item = (K, V)> + Extend<(K, V)> {
     fn get(&self, item: &K) -> Option<&V>;
     fn get_mut(&mut self, item: &K) -> Option<&mut V>;
     fn insert(&mut self, item: K, value: V);
@@ -16,11 +48,91 @@ pub trait Map<K, V>: Default + FromIterator<(K, V)> + IntoIterator<Item = (K, V)
         self.remove_entry(item).map(|(_, v)| v)
     }
     fn remove_entry(&mut self, item: &K) -> Option<(K, V)>;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn clear(&mut self);
+    /// Keeps only the entries for which `f` returns true.
+    fn retain(&mut self, f: impl FnMut(&K, &mut V) -> bool);
     fn keys<'a>(&'a self) -> impl Iterator<Item = &'a K> where K: 'a;
     fn values<'a>(&'a self) -> impl Iterator<Item = &'a V> where V: 'a;
     fn values_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut V> where V: 'a;
     fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)> where K: 'a, V: 'a;
     fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = (&'a K, &'a mut V)> where K: 'a, V: 'a;
+
+    type Occupied<'a>: OccupiedEntry<'a, K, V> where Self: 'a;
+    type Vacant<'a>: VacantEntry<'a, K, V> where Self: 'a;
+    /// Gets the entry of the key for in-place manipulation with a single lookup.
+    fn entry(&mut self, item: K) -> Entry<'_, K, V, Self>;
+}
+
+pub enum Entry<'a, K, V, M: Map<K, V> + 'a> {
+    Occupied(M::Occupied<'a>),
+    Vacant(M::Vacant<'a>),
+}
+
+pub trait OccupiedEntry<'a, K, V> {
+    fn key(&self) -> &K;
+    fn get(&self) -> &V;
+    fn get_mut(&mut self) -> &mut V;
+    fn into_mut(self) -> &'a mut V;
+    /// Replaces the value and returns the old one.
+    fn insert(&mut self, value: V) -> V;
+    fn remove_entry(self) -> (K, V);
+    fn remove(self) -> V where Self: Sized {
+        self.remove_entry().1
+    }
+}
+
+pub trait VacantEntry<'a, K, V> {
+    fn key(&self) -> &K;
+    fn into_key(self) -> K;
+    fn insert(self, value: V) -> &'a mut V;
+}
+
+impl<'a, K, V, M: Map<K, V> + 'a> Entry<'a, K, V, M> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_insert_with_key(self, default: impl FnOnce(&K) -> V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V where V: Default {
+        self.or_insert_with(Default::default)
+    }
+
+    pub fn and_modify(mut self, f: impl FnOnce(&mut V)) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
 }
 
 pub trait MapConstructor<K> {
@@ -64,6 +176,22 @@ impl<K: Eq + Hash, V, S: BuildHasher + Default> Map<K, V> for HashMap<K, V, S> {
         self.remove_entry(item)
     }
 
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.clear()
+    }
+
+    fn retain(&mut self, f: impl FnMut(&K, &mut V) -> bool) {
+        self.retain(f)
+    }
+
     #[allow(refining_impl_trait)]
     fn keys<'a>(&'a self) -> impl ExactSizeIterator<Item = &'a K> + FusedIterator where K: 'a {
         self.keys()
@@ -88,6 +216,33 @@ impl<K: Eq + Hash, V, S: BuildHasher + Default> Map<K, V> for HashMap<K, V, S> {
     fn iter_mut<'a>(&'a mut self) -> impl ExactSizeIterator<Item = (&'a K, &'a mut V)> + FusedIterator where K: 'a, V: 'a {
         self.iter_mut()
     }
+
+    type Occupied<'a> = hash_map::OccupiedEntry<'a, K, V> where Self: 'a;
+    type Vacant<'a> = hash_map::VacantEntry<'a, K, V> where Self: 'a;
+
+    fn entry(&mut self, item: K) -> Entry<'_, K, V, Self> {
+        match self.entry(item) {
+            hash_map::Entry::Occupied(entry) => Entry::Occupied(entry),
+            hash_map::Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, K, V> OccupiedEntry<'a, K, V> for hash_map::OccupiedEntry<'a, K, V> {
+    fn key(&self) -> &K { self.key() }
+    fn get(&self) -> &V { self.get() }
+    fn get_mut(&mut self) -> &mut V { self.get_mut() }
+    fn into_mut(self) -> &'a mut V { self.into_mut() }
+    fn insert(&mut self, value: V) -> V { self.insert(value) }
+    fn remove_entry(self) -> (K, V) { self.remove_entry() }
+}
+
+#[cfg(feature = "std")]
+impl<'a, K, V> VacantEntry<'a, K, V> for hash_map::VacantEntry<'a, K, V> {
+    fn key(&self) -> &K { self.key() }
+    fn into_key(self) -> K { self.into_key() }
+    fn insert(self, value: V) -> &'a mut V { self.insert(value) }
 }
 
 #[cfg(feature = "std")]
@@ -124,6 +279,22 @@ impl<K: Eq + Ord, V> Map<K, V> for BTreeMap<K, V> {
         self.remove_entry(item)
     }
 
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.clear()
+    }
+
+    fn retain(&mut self, f: impl FnMut(&K, &mut V) -> bool) {
+        self.retain(f)
+    }
+
     #[allow(refining_impl_trait)]
     fn keys<'a>(&'a self) -> impl ExactSizeIterator<Item = &'a K> + DoubleEndedIterator + FusedIterator where K: 'a {
         self.keys()
@@ -148,6 +319,33 @@ impl<K: Eq + Ord, V> Map<K, V> for BTreeMap<K, V> {
     fn iter_mut<'a>(&'a mut self) -> impl ExactSizeIterator<Item = (&'a K, &'a mut V)> + DoubleEndedIterator + FusedIterator where K: 'a, V: 'a {
         self.iter_mut()
     }
+
+    type Occupied<'a> = btree_map::OccupiedEntry<'a, K, V> where Self: 'a;
+    type Vacant<'a> = btree_map::VacantEntry<'a, K, V> where Self: 'a;
+
+    fn entry(&mut self, item: K) -> Entry<'_, K, V, Self> {
+        match self.entry(item) {
+            btree_map::Entry::Occupied(entry) => Entry::Occupied(entry),
+            btree_map::Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> for btree_map::OccupiedEntry<'a, K, V> {
+    fn key(&self) -> &K { self.key() }
+    fn get(&self) -> &V { self.get() }
+    fn get_mut(&mut self) -> &mut V { self.get_mut() }
+    fn into_mut(self) -> &'a mut V { self.into_mut() }
+    fn insert(&mut self, value: V) -> V { self.insert(value) }
+    fn remove_entry(self) -> (K, V) { self.remove_entry() }
+}
+
+#[cfg(feature = "std")]
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> for btree_map::VacantEntry<'a, K, V> {
+    fn key(&self) -> &K { self.key() }
+    fn into_key(self) -> K { self.into_key() }
+    fn insert(self, value: V) -> &'a mut V { self.insert(value) }
 }
 
 #[derive(Debug)]
@@ -197,6 +395,28 @@ impl<T, const N: usize> ArrayVec<T, N> {
             self.pop().unwrap()
         }
     }
+
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    /// Keeps only the elements for which `f` returns true, preserving their order.
+    pub fn retain_mut(&mut self, mut f: impl FnMut(&mut T) -> bool) {
+        let mut kept = 0;
+        for i in 0..self.len {
+            if f(unsafe { self.data[i].assume_init_mut() }) {
+                self.data.swap(kept, i);
+                kept += 1;
+            }
+        }
+        while self.len > kept {
+            self.pop();
+        }
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        self.retain_mut(|x| f(x))
+    }
 }
 
 impl<T, const N: usize> Default for ArrayVec<T, N> {
@@ -234,6 +454,14 @@ impl<T, const N: usize> FromIterator<T> for ArrayVec<T, N> {
     }
 }
 
+impl<T, const N: usize> Extend<T> for ArrayVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
 impl<T, const N: usize> Clone for ArrayVec<T, N> where T: Clone {
     fn clone(&self) -> Self {
         self.iter().cloned().collect()
@@ -336,6 +564,18 @@ impl<K: Eq, V, const N: usize> Map<K, V> for ArrayMap<K, V, N> {
         Some(self.data.swap_remove(index))
     }
 
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn clear(&mut self) {
+        self.data.clear()
+    }
+
+    fn retain(&mut self, mut f: impl FnMut(&K, &mut V) -> bool) {
+        self.data.retain_mut(|(k, v)| f(k, v))
+    }
+
     #[allow(refining_impl_trait)]
     fn keys<'a>(&'a self) -> impl ExactSizeIterator<Item = &'a K> + DoubleEndedIterator + FusedIterator where K: 'a {
         self.data.iter().map(|(k, _)| k)
@@ -360,6 +600,44 @@ impl<K: Eq, V, const N: usize> Map<K, V> for ArrayMap<K, V, N> {
     fn iter_mut<'a>(&'a mut self) -> impl ExactSizeIterator<Item = (&'a K, &'a mut V)> + DoubleEndedIterator + FusedIterator where K: 'a, V: 'a {
         self.data.iter_mut().map(|(k, v)| (&*k, v))
     }
+
+    type Occupied<'a> = ArrayMapOccupiedEntry<'a, K, V, N> where Self: 'a;
+    type Vacant<'a> = ArrayMapVacantEntry<'a, K, V, N> where Self: 'a;
+
+    fn entry(&mut self, item: K) -> Entry<'_, K, V, Self> {
+        match self.data.iter().position(|(k, _)| k == &item) {
+            Some(index) => Entry::Occupied(ArrayMapOccupiedEntry { data: &mut self.data, index }),
+            None => Entry::Vacant(ArrayMapVacantEntry { data: &mut self.data, key: item }),
+        }
+    }
+}
+
+pub struct ArrayMapOccupiedEntry<'a, K, V, const N: usize> {
+    data: &'a mut ArrayVec<(K, V), N>,
+    index: usize,
+}
+
+impl<'a, K, V, const N: usize> OccupiedEntry<'a, K, V> for ArrayMapOccupiedEntry<'a, K, V, N> {
+    fn key(&self) -> &K { &self.data[self.index].0 }
+    fn get(&self) -> &V { &self.data[self.index].1 }
+    fn get_mut(&mut self) -> &mut V { &mut self.data[self.index].1 }
+    fn into_mut(self) -> &'a mut V { &mut self.data[self.index].1 }
+    fn insert(&mut self, value: V) -> V { core::mem::replace(self.get_mut(), value) }
+    fn remove_entry(self) -> (K, V) { self.data.swap_remove(self.index) }
+}
+
+pub struct ArrayMapVacantEntry<'a, K, V, const N: usize> {
+    data: &'a mut ArrayVec<(K, V), N>,
+    key: K,
+}
+
+impl<'a, K, V, const N: usize> VacantEntry<'a, K, V> for ArrayMapVacantEntry<'a, K, V, N> {
+    fn key(&self) -> &K { &self.key }
+    fn into_key(self) -> K { self.key }
+    fn insert(self, value: V) -> &'a mut V {
+        self.data.push((self.key, value));
+        &mut self.data.last_mut().unwrap().1
+    }
 }
 
 impl<K: Eq, V, const N: usize> FromIterator<(K, V)> for ArrayMap<K, V, N> {
@@ -372,6 +650,14 @@ impl<K: Eq, V, const N: usize> FromIterator<(K, V)> for ArrayMap<K, V, N> {
     }
 }
 
+impl<K: Eq, V, const N: usize> Extend<(K, V)> for ArrayMap<K, V, N> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
 impl<K: Eq, V, const N: usize> IntoIterator for ArrayMap<K, V, N> {
     type Item = (K, V);
     type IntoIter = ArrayVecIntoIter<(K, V), N>;
@@ -386,12 +672,46 @@ impl<K: Eq, const N: usize> MapConstructor<K> for ArrayMapConstructor<N> {
     type Map<V> = ArrayMap<K, V, N>;
 }
 
-pub trait Set<T>: Default + FromIterator<T> {
+pub trait Set<T>: Default + FromIterator<T> + Extend<T> {
     fn contains(&self, item: &T) -> bool;
     /// Returns true if the item is newly inserted (i.e. true means the item was not present).
     fn insert(&mut self, item: T) -> bool;
     /// Returns true if the item is removed (i.e. true means the item was present).
     fn remove(&mut self, item: &T) -> bool;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn clear(&mut self);
+    /// Keeps only the items for which `f` returns true.
+    fn retain(&mut self, f: impl FnMut(&T) -> bool);
+    /// Items are yielded either by reference or by value (e.g. `BitSet` materializes them from bit positions).
+    fn iter<'a>(&'a self) -> impl Iterator<Item = impl Borrow<T> + 'a> where T: 'a;
+
+    /// Inserts all items of `other` into `self`.
+    fn union_with(&mut self, other: &Self) where T: Clone {
+        for item in other.iter() {
+            self.insert(item.borrow().clone());
+        }
+    }
+    /// Keeps only the items that are also in `other`.
+    fn intersect_with(&mut self, other: &Self) {
+        self.retain(|item| other.contains(item))
+    }
+    /// Removes the items that are in `other`.
+    fn difference_with(&mut self, other: &Self) {
+        self.retain(|item| !other.contains(item))
+    }
+    fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.iter().all(|item| other.contains(item.borrow()))
+    }
+    fn is_disjoint(&self, other: &Self) -> bool {
+        if self.len() <= other.len() {
+            self.iter().all(|item| !other.contains(item.borrow()))
+        } else {
+            other.iter().all(|item| !self.contains(item.borrow()))
+        }
+    }
 }
 
 pub trait SetConstructor<T> {
@@ -414,6 +734,35 @@ impl<T: Eq + Hash, S: BuildHasher + Default> Set<T> for std::collections::HashSe
     fn remove(&mut self, item: &T) -> bool {
         self.remove(item)
     }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.clear()
+    }
+
+    fn retain(&mut self, f: impl FnMut(&T) -> bool) {
+        self.retain(f)
+    }
+
+    #[allow(refining_impl_trait)]
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = &'a T> + FusedIterator where T: 'a {
+        self.iter()
+    }
+
+    fn is_subset(&self, other: &Self) -> bool {
+        self.is_subset(other)
+    }
+
+    fn is_disjoint(&self, other: &Self) -> bool {
+        self.is_disjoint(other)
+    }
 }
 
 #[cfg(feature = "std")]
@@ -429,6 +778,56 @@ impl<T: Eq + Ord> Set<T> for std::collections::BTreeSet<T> {
     fn remove(&mut self, item: &T) -> bool {
         self.remove(item)
     }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.clear()
+    }
+
+    fn retain(&mut self, f: impl FnMut(&T) -> bool) {
+        self.retain(f)
+    }
+
+    #[allow(refining_impl_trait)]
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = &'a T> + FusedIterator where T: 'a {
+        self.iter()
+    }
+
+    // merge-based fast paths: both sets are walked in ascending order
+    fn union_with(&mut self, other: &Self) where T: Clone {
+        self.append(&mut other.clone())
+    }
+
+    fn intersect_with(&mut self, other: &Self) {
+        let mut others = other.iter().peekable();
+        self.retain(|item| {
+            while others.next_if(|x| *x < item).is_some() {}
+            others.peek() == Some(&item)
+        })
+    }
+
+    fn difference_with(&mut self, other: &Self) {
+        let mut others = other.iter().peekable();
+        self.retain(|item| {
+            while others.next_if(|x| *x < item).is_some() {}
+            others.peek() != Some(&item)
+        })
+    }
+
+    fn is_subset(&self, other: &Self) -> bool {
+        self.is_subset(other)
+    }
+
+    fn is_disjoint(&self, other: &Self) -> bool {
+        self.is_disjoint(other)
+    }
 }
 
 #[repr(transparent)]
@@ -469,6 +868,31 @@ impl<T: Eq, const N: usize> Set<T> for ArraySet<T, N> {
     fn remove(&mut self, item: &T) -> bool {
         self.0.remove(item).is_some()
     }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        self.0.retain(|k, _| f(k))
+    }
+
+    #[allow(refining_impl_trait)]
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = &'a T> + DoubleEndedIterator + FusedIterator where T: 'a {
+        self.0.keys()
+    }
+}
+
+impl<T: Eq, const N: usize> Extend<T> for ArraySet<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert(item);
+        }
+    }
 }
 
 #[cfg(feature = "std")]
@@ -536,6 +960,131 @@ mod tests {
         bar::<ArrayMapConstructor<3>>();
     }
 
+    #[test]
+    fn test_map_entry() {
+        fn foo<M: MapConstructor<&'static str>>() {
+            let mut map = M::new();
+            for word in ["a", "b", "a", "c", "a", "b"] {
+                *map.entry(word).or_insert(0) += 1;
+            }
+            assert_eq!(map.get(&"a"), Some(&3));
+            assert_eq!(map.get(&"b"), Some(&2));
+            assert_eq!(map.get(&"c"), Some(&1));
+
+            map.entry("c").and_modify(|v| *v *= 10).or_insert(0);
+            map.entry("d").and_modify(|v| *v *= 10).or_insert_with(|| 7);
+            assert_eq!(map.get(&"c"), Some(&10));
+            assert_eq!(map.get(&"d"), Some(&7));
+
+            match map.entry("a") {
+                Entry::Occupied(entry) => assert_eq!(entry.remove_entry(), ("a", 3)),
+                Entry::Vacant(_) => unreachable!(),
+            }
+            match map.entry("a") {
+                Entry::Occupied(_) => unreachable!(),
+                Entry::Vacant(entry) => assert_eq!(entry.into_key(), "a"),
+            }
+            assert!(!map.contains_key(&"a"));
+        }
+
+        #[cfg(feature = "std")]
+        foo::<HashMapConstructor>();
+        #[cfg(feature = "std")]
+        foo::<BTreeMapConstructor>();
+        foo::<ArrayMapConstructor<4>>();
+    }
+
+    #[test]
+    fn test_map_size_and_retain() {
+        fn foo<M: MapConstructor<usize>>() {
+            let mut map = M::new();
+            assert!(map.is_empty());
+            map.extend((0..6).map(|i| (i, i * 10)));
+            assert_eq!(map.len(), 6);
+            map.retain(|k, v| {
+                *v += 1;
+                k % 2 == 0
+            });
+            assert_eq!(map.len(), 3);
+            assert_eq!(map.get(&2), Some(&21));
+            assert_eq!(map.get(&3), None);
+            map.clear();
+            assert!(map.is_empty());
+        }
+
+        #[cfg(feature = "std")]
+        foo::<HashMapConstructor>();
+        #[cfg(feature = "std")]
+        foo::<BTreeMapConstructor>();
+        foo::<ArrayMapConstructor<6>>();
+    }
+
+    #[test]
+    fn test_set_size_retain_and_iter() {
+        fn foo<S: SetConstructor<usize>>() {
+            let mut set = S::new();
+            assert!(set.is_empty());
+            set.extend([3, 1, 4, 1, 5]);
+            assert_eq!(set.len(), 4);
+            assert_eq!(set.iter().map(|x| *x.borrow()).sum::<usize>(), 13);
+            set.retain(|&x| x != 4);
+            assert_eq!(set.len(), 3);
+            assert!(!set.contains(&4));
+            set.clear();
+            assert!(set.is_empty());
+            assert_eq!(set.iter().count(), 0);
+        }
+
+        #[cfg(feature = "std")]
+        foo::<HashSetConstructor>();
+        #[cfg(feature = "std")]
+        foo::<BTreeSetConstructor>();
+        foo::<ArraySetConstructor<5>>();
+        #[cfg(feature = "std")]
+        foo::<crate::BitSetConstructor>();
+        foo::<crate::BitSetConstructor<[u8; 2]>>();
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        fn foo<S: SetConstructor<usize>>() {
+            let a: S::Set = [1, 2, 3, 4].into_iter().collect();
+            let b: S::Set = [3, 4, 5].into_iter().collect();
+            let c: S::Set = [7].into_iter().collect();
+
+            let mut union = S::new();
+            union.union_with(&a);
+            union.union_with(&b);
+            assert_eq!(union.len(), 5);
+            assert!(a.is_subset(&union) && b.is_subset(&union));
+            assert!(!union.is_subset(&a));
+
+            let mut intersection: S::Set = a.iter().map(|x| *x.borrow()).collect();
+            intersection.intersect_with(&b);
+            assert_eq!(intersection.len(), 2);
+            assert!(intersection.contains(&3) && intersection.contains(&4));
+
+            let mut difference: S::Set = a.iter().map(|x| *x.borrow()).collect();
+            difference.difference_with(&b);
+            assert_eq!(difference.len(), 2);
+            assert!(difference.contains(&1) && difference.contains(&2));
+
+            assert!(!a.is_disjoint(&b));
+            assert!(difference.is_disjoint(&b));
+            assert!(c.is_disjoint(&a) && a.is_disjoint(&c));
+            assert!(S::new().is_subset(&c));
+        }
+
+        #[cfg(feature = "std")]
+        foo::<HashSetConstructor>();
+        #[cfg(feature = "std")]
+        foo::<BTreeSetConstructor>();
+        foo::<ArraySetConstructor<5>>();
+        #[cfg(feature = "std")]
+        foo::<crate::BitSetConstructor>();
+        foo::<crate::BitSetConstructor<[u8; 1]>>();
+    }
+
     #[test]
     fn test_set_constructors() {
         fn foo<S: SetConstructor<usize>>() {
@@ -584,6 +1133,11 @@ mod tests {
         vec.push(3);
         assert_eq!(vec.swap_remove(0), 1);
         assert_eq!(vec.as_ref(), &[3, 2]);
+        vec.push(4);
+        vec.retain(|&x| x != 2);
+        assert_eq!(vec.as_ref(), &[3, 4]);
+        vec.clear();
+        assert!(vec.is_empty());
     }
 
     #[test]
@@ -633,4 +1187,11 @@ mod tests {
         assert_eq!(iter.next_back(), Some((2, 7)));
         assert_eq!(&iter.collect::<ArrayVec<_, 3>>()[..], &[(3, 9)]);
     }
+
+    #[test]
+    fn test_double_hashing() {
+        // a hash whose upper half is zero must still probe k different indices
+        let indices = double_hashing(12345, 5, 1000).collect::<ArrayVec<_, 5>>();
+        assert_eq!(&indices[..], &[345, 346, 347, 348, 349]);
+    }
 }
@@ -0,0 +1,163 @@
+#[cfg(feature = "std")]
+use super::{Map, MapConstructor};
+#[cfg(feature = "std")]
+use super::HashMapConstructor;
+
+/// A map that associates each key with a list of values, kept in insertion order.
+/// `C` chooses the map backend, e.g. `HashMapConstructor`, `BTreeMapConstructor` or `ArrayMapConstructor<N>`.
+#[cfg(feature = "std")]
+pub struct MultiMap<K, V, C: MapConstructor<K> = HashMapConstructor> {
+    data: C::Map<Vec<V>>,
+    len: usize,
+}
+
+#[cfg(feature = "std")]
+impl<K, V, C: MapConstructor<K>> MultiMap<K, V, C> {
+    pub fn new() -> Self {
+        MultiMap { data: C::new(), len: 0 }
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.data.entry(key).or_default().push(value);
+        self.len += 1;
+    }
+
+    /// Returns the values of the key, or an empty slice if the key is absent.
+    pub fn get(&self, key: &K) -> &[V] {
+        self.data.get(key).map(|values| &values[..]).unwrap_or_default()
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.data.contains_key(key)
+    }
+
+    /// Removes the key with all its values.
+    pub fn remove(&mut self, key: &K) -> Option<Vec<V>> {
+        let values = self.data.remove(key)?;
+        self.len -= values.len();
+        Some(values)
+    }
+
+    /// Removes the first occurrence of the value under the key. Returns true if it was present.
+    pub fn remove_value(&mut self, key: &K, value: &V) -> bool where V: PartialEq {
+        let Some(values) = self.data.get_mut(key) else { return false };
+        let Some(index) = values.iter().position(|v| v == value) else { return false };
+        values.remove(index);
+        if values.is_empty() {
+            self.data.remove(key);
+        }
+        self.len -= 1;
+        true
+    }
+
+    /// Keeps only the pairs for which `f` returns true.
+    pub fn retain(&mut self, mut f: impl FnMut(&K, &V) -> bool) {
+        let mut removed = 0;
+        self.data.retain(|k, values| {
+            let before = values.len();
+            values.retain(|v| f(k, v));
+            removed += before - values.len();
+            !values.is_empty()
+        });
+        self.len -= removed;
+    }
+
+    /// The number of key-value pairs.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The number of distinct keys.
+    pub fn keys_len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.len = 0;
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.data.keys()
+    }
+
+    /// Iterates the keys together with all their values.
+    pub fn iter_groups(&self) -> impl Iterator<Item = (&K, &[V])> {
+        self.data.iter().map(|(k, values)| (k, &values[..]))
+    }
+
+    /// Iterates all key-value pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.data.iter().flat_map(|(k, values)| values.iter().map(move |v| (k, v)))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, C: MapConstructor<K>> Default for MultiMap<K, V, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, C: MapConstructor<K>> Clone for MultiMap<K, V, C> where C::Map<Vec<V>>: Clone {
+    fn clone(&self) -> Self {
+        MultiMap { data: self.data.clone(), len: self.len }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, C: MapConstructor<K>> Extend<(K, V)> for MultiMap<K, V, C> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, C: MapConstructor<K>> FromIterator<(K, V)> for MultiMap<K, V, C> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArrayMapConstructor, BTreeMapConstructor};
+
+    #[test]
+    fn test_multimap() {
+        fn foo<C: MapConstructor<&'static str>>() {
+            let docs = [("rust", 1), ("absurd", 1), ("rust", 2), ("rust", 3), ("absurd", 3)];
+            let mut index: MultiMap<_, _, C> = docs.into_iter().collect();
+            assert_eq!(index.len(), 5);
+            assert_eq!(index.keys_len(), 2);
+            assert_eq!(index.get(&"rust"), &[1, 2, 3]);
+            assert_eq!(index.get(&"crate"), &[]);
+            assert_eq!(index.iter().filter(|(_, v)| **v == 3).count(), 2);
+
+            assert!(index.remove_value(&"rust", &2));
+            assert!(!index.remove_value(&"rust", &2));
+            assert_eq!(index.get(&"rust"), &[1, 3]);
+
+            index.retain(|_, v| *v != 1);
+            assert_eq!(index.len(), 2);
+            assert_eq!(index.remove(&"absurd"), Some(vec![3]));
+            assert!(index.remove_value(&"rust", &3));
+            assert!(index.is_empty() && !index.contains_key(&"rust"));
+        }
+
+        foo::<HashMapConstructor>();
+        foo::<BTreeMapConstructor>();
+        foo::<ArrayMapConstructor<2>>();
+    }
+}
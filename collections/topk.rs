@@ -0,0 +1,246 @@
+#[cfg(feature = "std")]
+use super::{Map, MapConstructor};
+#[cfg(feature = "std")]
+use super::HashMapConstructor;
+
+/// Space-Saving summary of the most frequent keys of a stream (Metwally, Agrawal and El Abbadi, 2005).
+/// Monitors at most `capacity` keys; an unmonitored key replaces the one with the smallest count and inherits
+/// that count as its error. Every key occurring more than `total / capacity` times is monitored, and a monitored
+/// key's true count is within `count - error ..= count`.
+/// `C` chooses the map backend of the key index, e.g. `HashMapConstructor` or `BTreeMapConstructor`.
+#[cfg(feature = "std")]
+pub struct TopK<K, C: MapConstructor<K> = HashMapConstructor> {
+    index: C::Map<usize>, // key -> position in `counters`
+    counters: Vec<TopKCounter<K>>, // binary min-heap by count
+    capacity: usize,
+    total: usize,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+struct TopKCounter<K> {
+    key: K,
+    count: usize,
+    error: usize,
+}
+
+#[cfg(feature = "std")]
+impl<K: Clone, C: MapConstructor<K>> TopK<K, C> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0);
+        TopK { index: C::new(), counters: Vec::with_capacity(capacity), capacity, total: 0 }
+    }
+
+    pub fn observe(&mut self, key: K) {
+        self.observe_n(key, 1)
+    }
+
+    pub fn observe_n(&mut self, key: K, n: usize) {
+        self.total += n;
+        if let Some(&i) = self.index.get(&key) {
+            self.counters[i].count += n;
+            self.sift_down(i);
+        } else if self.counters.len() < self.capacity {
+            self.index.insert(key.clone(), self.counters.len());
+            self.counters.push(TopKCounter { key, count: n, error: 0 });
+            self.sift_up(self.counters.len() - 1);
+        } else {
+            let min = self.counters[0].count;
+            self.index.remove(&self.counters[0].key);
+            self.index.insert(key.clone(), 0);
+            self.counters[0] = TopKCounter { key, count: min + n, error: min };
+            self.sift_down(0);
+        }
+    }
+
+    /// Returns the estimated count and its maximum overestimation if the key is monitored.
+    pub fn get(&self, key: &K) -> Option<(usize, usize)> {
+        self.index.get(key).map(|&i| (self.counters[i].count, self.counters[i].error))
+    }
+
+    /// Returns up to `k` monitored keys as `(key, count, error)` in descending order of count. Ties are broken arbitrarily.
+    pub fn top(&self, k: usize) -> Vec<(&K, usize, usize)> {
+        let mut results: Vec<_> = self.iter().collect();
+        results.sort_unstable_by_key(|&(_, count, _)| core::cmp::Reverse(count));
+        results.truncate(k);
+        results
+    }
+
+    /// Merges another summary into this one (Agarwal et al., 2012). Keys missing from a full summary are assumed
+    /// to have its minimum count, and only the `capacity` largest counts are kept.
+    pub fn merge(&mut self, other: &Self) {
+        let (self_min, other_min) = (self.min_count(), other.min_count());
+        let mut matched = vec![false; self.counters.len()];
+        let mut merged = vec![];
+        for counter in &other.counters {
+            if let Some(&i) = self.index.get(&counter.key) {
+                self.counters[i].count += counter.count;
+                self.counters[i].error += counter.error;
+                matched[i] = true;
+            } else {
+                merged.push(TopKCounter { key: counter.key.clone(), count: counter.count + self_min, error: counter.error + self_min });
+            }
+        }
+        for (counter, matched) in self.counters.iter_mut().zip(matched) {
+            if !matched {
+                counter.count += other_min;
+                counter.error += other_min;
+            }
+        }
+
+        merged.append(&mut self.counters);
+        merged.sort_unstable_by_key(|counter| core::cmp::Reverse(counter.count));
+        merged.truncate(self.capacity);
+        merged.reverse(); // ascending order is a valid min-heap
+
+        self.index.clear();
+        for (i, counter) in merged.iter().enumerate() {
+            self.index.insert(counter.key.clone(), i);
+        }
+        self.counters = merged;
+        self.total += other.total;
+    }
+
+    // the count that an unmonitored key may have
+    fn min_count(&self) -> usize {
+        if self.counters.len() < self.capacity { 0 } else { self.counters[0].count }
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.counters.swap(i, j);
+        *self.index.get_mut(&self.counters[i].key).unwrap() = i;
+        *self.index.get_mut(&self.counters[j].key).unwrap() = j;
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 && self.counters[i].count < self.counters[(i - 1) / 2].count {
+            self.swap(i, (i - 1) / 2);
+            i = (i - 1) / 2;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let smallest = [2 * i + 1, 2 * i + 2].into_iter()
+                .filter(|&child| child < self.counters.len())
+                .fold(i, |smallest, child| if self.counters[child].count < self.counters[smallest].count { child } else { smallest });
+            if smallest == i {
+                return
+            }
+            self.swap(i, smallest);
+            i = smallest;
+        }
+    }
+
+    /// The number of monitored keys.
+    pub fn len(&self) -> usize {
+        self.counters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The sum of all observed counts.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn clear(&mut self) {
+        self.index.clear();
+        self.counters.clear();
+        self.total = 0;
+    }
+
+    /// Iterates the monitored keys as `(key, count, error)` in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, usize, usize)> {
+        self.counters.iter().map(|counter| (&counter.key, counter.count, counter.error))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Clone, C: MapConstructor<K>> Clone for TopK<K, C> where C::Map<usize>: Clone {
+    fn clone(&self) -> Self {
+        TopK { index: self.index.clone(), counters: self.counters.clone(), capacity: self.capacity, total: self.total }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Clone, C: MapConstructor<K>> Extend<K> for TopK<K, C> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for key in iter {
+            self.observe(key);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BTreeMapConstructor;
+
+    // key i in 0..10 occurs ceil(1000 / (i + 1)) times, interleaved with 5000 keys occurring once
+    fn stream() -> impl Iterator<Item = u32> {
+        (0..5000).flat_map(|n| (0..10u32).filter(move |i| n % (5 * (i + 1)) == 0).chain([1000 + n]))
+    }
+
+    fn true_count(key: u32) -> usize {
+        if key < 10 { 1000usize.div_ceil(key as usize + 1) } else { 1 }
+    }
+
+    #[test]
+    fn test_topk() {
+        fn foo<C: MapConstructor<u32>>() {
+            let mut topk: TopK<u32, C> = TopK::new(50);
+            topk.extend(stream());
+            assert_eq!(topk.len(), 50);
+            assert_eq!(topk.total(), 5000 + (0..10).map(true_count).sum::<usize>());
+
+            let top = topk.top(3);
+            assert_eq!(top.iter().map(|t| *t.0).collect::<Vec<_>>(), [0, 1, 2]);
+            for (&key, count, error) in topk.iter() {
+                assert!(count - error <= true_count(key) && true_count(key) <= count);
+            }
+            for key in (0..10).filter(|&key| true_count(key) > topk.total() / topk.capacity()) {
+                assert!(topk.get(&key).is_some());
+            }
+            assert!(topk.top(100).windows(2).all(|w| w[0].1 >= w[1].1));
+
+            topk.clear();
+            assert!(topk.is_empty() && topk.top(1).is_empty());
+        }
+
+        foo::<HashMapConstructor>();
+        foo::<BTreeMapConstructor>();
+    }
+
+    #[test]
+    fn test_topk_merge() {
+        let (mut a, mut b): (TopK<u32>, TopK<u32>) = (TopK::new(40), TopK::new(40));
+        for (n, key) in stream().enumerate() {
+            if n % 2 == 0 { a.observe(key) } else { b.observe(key) }
+        }
+        let total = a.total() + b.total();
+        a.merge(&b);
+        assert_eq!(a.len(), 40);
+        assert_eq!(a.total(), total);
+        assert_eq!(a.top(4).iter().map(|t| *t.0).collect::<Vec<_>>(), [0, 1, 2, 3]);
+        for (&key, count, error) in a.iter() {
+            assert!(count - error <= true_count(key) && true_count(key) <= count);
+        }
+
+        let mut small: TopK<&str> = TopK::new(3);
+        small.observe_n("x", 5);
+        let mut other = TopK::new(3);
+        other.observe_n("x", 2);
+        other.observe("y");
+        small.merge(&other);
+        assert_eq!(small.get(&"x"), Some((7, 0)));
+        assert_eq!(small.get(&"y"), Some((1, 0)));
+    }
+}
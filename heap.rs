@@ -1,124 +1,1065 @@
 #[cfg(feature = "std")]
 use std::collections::BinaryHeap;
+#[cfg(feature = "std")]
+use core::{cell::{Cell, UnsafeCell}, mem::ManuallyDrop};
+#[cfg(feature = "std")]
+use crate::{Map, MapConstructor, HashMapConstructor, Integer, zero, Arena};
 
+/// a binary min-heap of `data` ordered by `priority`. Ties pop in arbitrary order, or in insertion order if the heap
+/// is created with `new_fifo` or `with_capacity_fifo`. Incomparable priorities never panic: a priority that is not
+/// comparable to itself (NaN) sorts after all others. Use `TotalOrd<f32>` or `TotalOrd<f64>` to order floats by `total_cmp`.
 #[cfg(feature = "std")]
 #[derive(Debug, Clone)]
-pub struct MinHeap<T, S: PartialOrd = f64>(BinaryHeap<MinHeapEntry<T, S>>);
+pub struct MinHeap<T, S: PartialOrd = f64, Q: TieBreak = ()> {
+    heap: BinaryHeap<MinHeapEntry<T, S, Q>>,
+    next_seq: Q,
+}
+
+/// the secondary key of `MinHeap` entries: `()` leaves ties in arbitrary order at no cost, and `u64` counts insertions
+/// so that ties pop in insertion order.
+#[cfg(feature = "std")]
+pub trait TieBreak: Ord + Copy + Default {
+    fn next(&mut self) -> Self;
+}
+
+#[cfg(feature = "std")]
+impl TieBreak for () {
+    fn next(&mut self) -> Self {}
+}
+
+#[cfg(feature = "std")]
+impl TieBreak for u64 {
+    fn next(&mut self) -> Self {
+        *self += 1;
+        *self - 1
+    }
+}
 
 #[cfg(feature = "std")]
 #[derive(Debug, Clone)]
-struct MinHeapEntry<T, S: PartialOrd> {
+struct MinHeapEntry<T, S: PartialOrd, Q> {
     data: T,
     priority: S,
+    seq: Q,
 }
 
+// a total order extending `partial_cmp`, where values incomparable to themselves are the largest
 #[cfg(feature = "std")]
-impl<T, S: PartialOrd> Ord for MinHeapEntry<T, S> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+fn cmp_priority<S: PartialOrd>(a: &S, b: &S) -> core::cmp::Ordering {
+    a.partial_cmp(b).unwrap_or_else(|| a.partial_cmp(a).is_none().cmp(&b.partial_cmp(b).is_none()))
+}
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd, Q: TieBreak> Ord for MinHeapEntry<T, S, Q> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        cmp_priority(&other.priority, &self.priority).then(other.seq.cmp(&self.seq))
     }
 }
 
 #[cfg(feature = "std")]
-impl<T, S: PartialOrd> PartialOrd for MinHeapEntry<T, S> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.priority.partial_cmp(&other.priority).map(|ord| ord.reverse())
+impl<T, S: PartialOrd, Q: TieBreak> PartialOrd for MinHeapEntry<T, S, Q> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
     }
 }
 
 #[cfg(feature = "std")]
-impl<T, S: PartialOrd> PartialEq for MinHeapEntry<T, S> {
+impl<T, S: PartialOrd, Q: TieBreak> PartialEq for MinHeapEntry<T, S, Q> {
     fn eq(&self, other: &Self) -> bool {
-        self.priority == other.priority
+        self.cmp(other).is_eq()
     }
 }
 
 #[cfg(feature = "std")]
-impl<T, S: PartialOrd> Eq for MinHeapEntry<T, S> {}
+impl<T, S: PartialOrd, Q: TieBreak> Eq for MinHeapEntry<T, S, Q> {}
 
 
 #[cfg(feature = "std")]
 impl<T, S: PartialOrd> MinHeap<T, S> {
     pub fn new() -> Self {
-        Self(BinaryHeap::new())
+        Self::with_capacity(0)
     }
 
     pub fn with_capacity(capacity: usize) -> Self {
-        Self(BinaryHeap::with_capacity(capacity))
+        Self { heap: BinaryHeap::with_capacity(capacity), next_seq: () }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd> MinHeap<T, S, u64> {
+    /// create a heap in which entries with equal priorities pop in insertion order
+    pub fn new_fifo() -> Self {
+        Self::with_capacity_fifo(0)
+    }
+
+    pub fn with_capacity_fifo(capacity: usize) -> Self {
+        Self { heap: BinaryHeap::with_capacity(capacity), next_seq: 0 }
     }
+}
 
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd, Q: TieBreak> MinHeap<T, S, Q> {
     pub fn push(&mut self, data: T, priority: S) {
-        self.0.push(MinHeapEntry { data, priority });
+        let seq = self.next_seq.next();
+        self.heap.push(MinHeapEntry { data, priority, seq });
     }
 
     pub fn pop(&mut self) -> Option<T> {
-        self.0.pop().map(|entry| entry.data)
+        self.heap.pop().map(|entry| entry.data)
     }
 
     pub fn pop_with_priority(&mut self) -> Option<(T, S)> {
-        self.0.pop().map(|entry| (entry.data, entry.priority))
+        self.heap.pop().map(|entry| (entry.data, entry.priority))
     }
 
     pub fn peek(&self) -> Option<&T> {
-        self.0.peek().map(|entry| &entry.data)
+        self.heap.peek().map(|entry| &entry.data)
     }
 
     pub fn peek_with_priority(&self) -> Option<(&T, &S)> {
-        self.0.peek().map(|entry| (&entry.data, &entry.priority))
+        self.heap.peek().map(|entry| (&entry.data, &entry.priority))
     }
 
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.heap.is_empty()
     }
 
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.heap.len()
     }
 
     pub fn clear(&mut self) {
-        self.0.clear()
+        self.heap.clear()
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &T> {
-        self.0.iter().map(|entry| &entry.data)
+        self.heap.iter().map(|entry| &entry.data)
     }
 
     pub fn iter_with_priority(&self) -> impl Iterator<Item = (&T, &S)> {
-        self.0.iter().map(|entry| (&entry.data, &entry.priority))
+        self.heap.iter().map(|entry| (&entry.data, &entry.priority))
     }
 
     // cannot implement IntoIterator due to unable to name the return type in the trait
     pub fn into_iter(self) -> impl Iterator<Item = (T, S)> {
-        self.0.into_iter().map(|entry| (entry.data, entry.priority))
+        self.heap.into_iter().map(|entry| (entry.data, entry.priority))
     }
 }
 
 #[cfg(feature = "std")]
-impl<T, S: PartialOrd> FromIterator<(T, S)> for MinHeap<T, S> {
+impl<T, S: PartialOrd, Q: TieBreak> FromIterator<(T, S)> for MinHeap<T, S, Q> {
     fn from_iter<I: IntoIterator<Item = (T, S)>>(iter: I) -> Self {
+        let mut heap = Self::default();
+        heap.extend(iter);
+        heap
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd, Q: TieBreak> Extend<(T, S)> for MinHeap<T, S, Q> {
+    fn extend<I: IntoIterator<Item = (T, S)>>(&mut self, iter: I) {
+        for (data, priority) in iter {
+            self.push(data, priority);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd, Q: TieBreak> Default for MinHeap<T, S, Q> { // deriving requires T to implement Default which is unnecessary
+    fn default() -> Self {
+        Self { heap: BinaryHeap::new(), next_seq: Q::default() }
+    }
+}
+
+macro_rules! impl_total_ord {
+    ($($float:ty),*) => {$(
+        impl PartialEq for TotalOrd<$float> {
+            fn eq(&self, other: &Self) -> bool {
+                self.0.total_cmp(&other.0).is_eq()
+            }
+        }
+
+        impl Eq for TotalOrd<$float> {}
+
+        impl PartialOrd for TotalOrd<$float> {
+            fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for TotalOrd<$float> {
+            fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+                self.0.total_cmp(&other.0)
+            }
+        }
+
+        impl From<$float> for TotalOrd<$float> {
+            fn from(x: $float) -> Self {
+                Self(x)
+            }
+        }
+    )*};
+}
+
+/// a float ordered by `total_cmp`: -NaN < -inf < ... < -0 < +0 < ... < +inf < NaN
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TotalOrd<F>(pub F);
+
+impl_total_ord!(f32, f64);
+
+/// an indexed binary min-heap with at most one entry per key, allowing the priority of a key to be changed and removed.
+/// `C` chooses the map from keys to their positions in the heap, e.g. `HashMapConstructor` or `BTreeMapConstructor`.
+#[cfg(feature = "std")]
+pub struct KeyedMinHeap<K, S: PartialOrd = f64, C: MapConstructor<K> = HashMapConstructor> {
+    entries: Vec<(K, S)>,
+    index: C::Map<usize>, // key -> position in `entries`
+}
+
+#[cfg(feature = "std")]
+impl<K: Clone, S: PartialOrd, C: MapConstructor<K>> KeyedMinHeap<K, S, C> {
+    pub fn new() -> Self {
+        Self { entries: Vec::new(), index: C::new() }
+    }
+
+    /// insert the key, or replace its priority. Return the old priority.
+    pub fn push(&mut self, key: K, priority: S) -> Option<S> {
+        match self.index.get(&key) {
+            Some(&i) => Some(self.set_priority(i, priority)),
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, priority));
+                self.sift_up(self.entries.len() - 1);
+                None
+            }
+        }
+    }
+
+    /// insert the key, or lower its priority if the new one is smaller. Return true if the heap changed.
+    pub fn push_or_decrease(&mut self, key: K, priority: S) -> bool {
+        match self.index.get(&key) {
+            Some(&i) if priority < self.entries[i].1 => {
+                self.set_priority(i, priority);
+                true
+            }
+            Some(_) => false,
+            None => {
+                self.push(key, priority);
+                true
+            }
+        }
+    }
+
+    /// change the priority of an existing key. Return the old priority, or None if the key is absent.
+    pub fn change_priority(&mut self, key: &K, priority: S) -> Option<S> {
+        let i = *self.index.get(key)?;
+        Some(self.set_priority(i, priority))
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<S> {
+        let i = self.index.remove(key)?;
+        let (_, priority) = self.entries.swap_remove(i);
+        if i < self.entries.len() {
+            *self.index.get_mut(&self.entries[i].0).unwrap() = i;
+            self.sift_up(i);
+            self.sift_down(i);
+        }
+        Some(priority)
+    }
+
+    pub fn priority_of(&self, key: &K) -> Option<&S> {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.index.contains_key(key)
+    }
+
+    pub fn pop(&mut self) -> Option<K> {
+        self.pop_with_priority().map(|(key, _)| key)
+    }
+
+    pub fn pop_with_priority(&mut self) -> Option<(K, S)> {
+        let key = self.entries.first()?.0.clone();
+        self.remove(&key).map(|priority| (key, priority))
+    }
+
+    pub fn peek(&self) -> Option<&K> {
+        self.entries.first().map(|(key, _)| key)
+    }
+
+    pub fn peek_with_priority(&self) -> Option<(&K, &S)> {
+        self.entries.first().map(|(key, priority)| (key, priority))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+    }
+
+    pub fn iter_with_priority(&self) -> impl Iterator<Item = (&K, &S)> {
+        self.entries.iter().map(|(key, priority)| (key, priority))
+    }
+
+    fn set_priority(&mut self, i: usize, priority: S) -> S {
+        let decreased = priority < self.entries[i].1;
+        let old = core::mem::replace(&mut self.entries[i].1, priority);
+        if decreased { self.sift_up(i) } else { self.sift_down(i) }
+        old
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.entries.swap(i, j);
+        *self.index.get_mut(&self.entries[i].0).unwrap() = i;
+        *self.index.get_mut(&self.entries[j].0).unwrap() = j;
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 && self.entries[i].1 < self.entries[(i - 1) / 2].1 {
+            self.swap(i, (i - 1) / 2);
+            i = (i - 1) / 2;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let smallest = [2 * i + 1, 2 * i + 2].into_iter()
+                .filter(|&child| child < self.entries.len())
+                .fold(i, |smallest, child| if self.entries[child].1 < self.entries[smallest].1 { child } else { smallest });
+            if smallest == i {
+                return
+            }
+            self.swap(i, smallest);
+            i = smallest;
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Clone, S: PartialOrd, C: MapConstructor<K>> Default for KeyedMinHeap<K, S, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Clone, S: PartialOrd + Clone, C: MapConstructor<K>> Clone for KeyedMinHeap<K, S, C> where C::Map<usize>: Clone {
+    fn clone(&self) -> Self {
+        Self { entries: self.entries.clone(), index: self.index.clone() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Clone, S: PartialOrd, C: MapConstructor<K>> Extend<(K, S)> for KeyedMinHeap<K, S, C> {
+    fn extend<I: IntoIterator<Item = (K, S)>>(&mut self, iter: I) {
+        for (key, priority) in iter {
+            self.push(key, priority);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Clone, S: PartialOrd, C: MapConstructor<K>> FromIterator<(K, S)> for KeyedMinHeap<K, S, C> {
+    fn from_iter<I: IntoIterator<Item = (K, S)>>(iter: I) -> Self {
         let mut heap = Self::new();
         heap.extend(iter);
         heap
     }
 }
 
+/// a monotone priority queue for integer priorities: a pushed priority must not be smaller than the last popped one,
+/// as is the case in Dijkstra's algorithm with non-negative costs. Entries are kept in buckets by the highest bit in
+/// which their priority differs from the last popped one, so each entry is moved at most once per bit, and no
+/// comparison is needed except when a bucket is redistributed.
 #[cfg(feature = "std")]
-impl<T, S: PartialOrd> Extend<(T, S)> for MinHeap<T, S> {
+#[derive(Debug, Clone)]
+pub struct RadixHeap<T, S: Integer> {
+    buckets: Vec<Vec<(T, S)>>, // bucket 0 holds the priorities equal to `last`, bucket i those with highest differing bit i - 1
+    last: S,
+    len: usize,
+}
+
+#[cfg(feature = "std")]
+impl<T, S: Integer> RadixHeap<T, S> {
+    pub fn new() -> Self {
+        let bits = core::mem::size_of::<S>() * 8;
+        Self { buckets: (0..=bits).map(|_| Vec::new()).collect(), last: zero(), len: 0 }
+    }
+
+    /// create a heap whose priorities are at least `min`, which can be negative for signed priorities
+    pub fn with_min(min: S) -> Self {
+        Self { last: min, ..Self::new() }
+    }
+
+    fn bucket(&self, priority: S) -> usize {
+        let bits = core::mem::size_of::<S>() * 8;
+        bits - (priority ^ self.last).leading_zeros() as usize
+    }
+
+    /// `priority` must not be smaller than the last popped priority
+    pub fn push(&mut self, data: T, priority: S) {
+        assert!(priority >= self.last, "RadixHeap priority is smaller than the last popped one");
+        let bucket = self.bucket(priority);
+        self.buckets[bucket].push((data, priority));
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.pop_with_priority().map(|(data, _)| data)
+    }
+
+    pub fn pop_with_priority(&mut self) -> Option<(T, S)> {
+        if self.buckets[0].is_empty() {
+            let i = self.buckets.iter().position(|bucket| !bucket.is_empty())?;
+            self.last = self.buckets[i].iter().map(|(_, priority)| *priority).reduce(|a, b| if b < a { b } else { a }).unwrap();
+            for (data, priority) in core::mem::take(&mut self.buckets[i]) {
+                let bucket = self.bucket(priority);
+                self.buckets[bucket].push((data, priority));
+            }
+        }
+        self.len -= 1;
+        self.buckets[0].pop()
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.peek_with_priority().map(|(data, _)| data)
+    }
+
+    pub fn peek_with_priority(&self) -> Option<(&T, &S)> {
+        let bucket = self.buckets.iter().find(|bucket| !bucket.is_empty())?;
+        bucket.iter().reduce(|a, b| if b.1 < a.1 { b } else { a }).map(|(data, priority)| (data, priority))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// remove all entries. Priorities pushed later must still not be smaller than the last popped one.
+    pub fn clear(&mut self) {
+        self.buckets.iter_mut().for_each(Vec::clear);
+        self.len = 0;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.iter_with_priority().map(|(data, _)| data)
+    }
+
+    pub fn iter_with_priority(&self) -> impl Iterator<Item = (&T, &S)> {
+        self.buckets.iter().flatten().map(|(data, priority)| (data, priority))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: Integer> FromIterator<(T, S)> for RadixHeap<T, S> {
+    fn from_iter<I: IntoIterator<Item = (T, S)>>(iter: I) -> Self {
+        let mut heap = Self::new();
+        heap.extend(iter);
+        heap
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: Integer> Extend<(T, S)> for RadixHeap<T, S> {
     fn extend<I: IntoIterator<Item = (T, S)>>(&mut self, iter: I) {
-        self.0.extend(iter.into_iter().map(|(data, priority)| MinHeapEntry { data, priority }));
+        for (data, priority) in iter {
+            self.push(data, priority);
+        }
     }
 }
 
 #[cfg(feature = "std")]
-impl<T, S: PartialOrd> Default for MinHeap<T, S> { // deriving requires T to implement Default which is unnecessary
+impl<T, S: Integer> IntoIterator for RadixHeap<T, S> {
+    type Item = (T, S);
+    type IntoIter = core::iter::Flatten<std::vec::IntoIter<Vec<(T, S)>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.buckets.into_iter().flatten()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: Integer> Default for RadixHeap<T, S> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-// TODO: keyed heap that allows changing priority and early removal?
-// https://docs.rs/keyed_priority_queue/latest/keyed_priority_queue/
-// https://docs.rs/priority-queue/latest/priority_queue/priority_queue/struct.PriorityQueue.html
+/// an implicit min-heap in which every node has `D` children. A larger `D` makes the tree shallower and `push` cheaper,
+/// and keeps the children of a node in the same cache lines, at the cost of more comparisons per level in `pop`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct DAryMinHeap<T, S: PartialOrd = f64, const D: usize = 4>(Vec<(T, S)>);
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd, const D: usize> DAryMinHeap<T, S, D> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        const { assert!(D >= 2) };
+        Self(Vec::with_capacity(capacity))
+    }
+
+    pub fn push(&mut self, data: T, priority: S) {
+        self.0.push((data, priority));
+        let mut i = self.0.len() - 1;
+        while i > 0 && self.0[i].1 < self.0[(i - 1) / D].1 {
+            self.0.swap(i, (i - 1) / D);
+            i = (i - 1) / D;
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.pop_with_priority().map(|(data, _)| data)
+    }
+
+    pub fn pop_with_priority(&mut self) -> Option<(T, S)> {
+        if self.0.is_empty() {
+            return None
+        }
+
+        let result = self.0.swap_remove(0);
+        let mut i = 0;
+        loop {
+            let children = (D * i + 1).min(self.0.len())..(D * i + D + 1).min(self.0.len());
+            let smallest = children.fold(i, |smallest, child| if self.0[child].1 < self.0[smallest].1 { child } else { smallest });
+            if smallest == i {
+                return Some(result)
+            }
+            self.0.swap(i, smallest);
+            i = smallest;
+        }
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.0.first().map(|(data, _)| data)
+    }
+
+    pub fn peek_with_priority(&self) -> Option<(&T, &S)> {
+        self.0.first().map(|(data, priority)| (data, priority))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.0.iter().map(|(data, _)| data)
+    }
+
+    pub fn iter_with_priority(&self) -> impl Iterator<Item = (&T, &S)> {
+        self.0.iter().map(|(data, priority)| (data, priority))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd, const D: usize> FromIterator<(T, S)> for DAryMinHeap<T, S, D> {
+    fn from_iter<I: IntoIterator<Item = (T, S)>>(iter: I) -> Self {
+        let mut heap = Self::new();
+        heap.extend(iter);
+        heap
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd, const D: usize> Extend<(T, S)> for DAryMinHeap<T, S, D> {
+    fn extend<I: IntoIterator<Item = (T, S)>>(&mut self, iter: I) {
+        for (data, priority) in iter {
+            self.push(data, priority);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd, const D: usize> IntoIterator for DAryMinHeap<T, S, D> {
+    type Item = (T, S);
+    type IntoIter = std::vec::IntoIter<(T, S)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd, const D: usize> Default for DAryMinHeap<T, S, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// a pairing heap whose nodes are allocated in an `Arena`, supporting O(1) `push` and `meld`, amortized O(log n) `pop`,
+/// and `decrease_priority` through the handles returned by `push`. Heaps sharing an arena can be melded without copying.
+/// The memory of popped nodes is reclaimed only when the arena is dropped, but the data is dropped with the heap.
+#[cfg(feature = "std")]
+pub struct PairingHeap<'a, T, S: PartialOrd = f64, const N: usize = 1024, const C: usize = 30> {
+    arena: &'a Arena<N, C>,
+    owner: &'a PairingHeapOwner<'a>,
+    root: Option<&'a PairingHeapNode<'a, T, S>>,
+    len: usize,
+}
+
+#[cfg(feature = "std")]
+struct PairingHeapNode<'a, T, S> {
+    data: ManuallyDrop<T>, // moved out when popped
+    priority: UnsafeCell<S>, // only changed through `&mut PairingHeap`
+    alive: Cell<bool>,
+    owner: Cell<&'a PairingHeapOwner<'a>>, // the heap it was pushed into, see `PairingHeap::owns`
+    child: Cell<Option<&'a PairingHeapNode<'a, T, S>>>, // the leftmost child
+    sibling: Cell<Option<&'a PairingHeapNode<'a, T, S>>>, // the next sibling to the right
+    prev: Cell<Option<&'a PairingHeapNode<'a, T, S>>>, // the parent of a leftmost child, otherwise the previous sibling
+}
+
+// identifies a heap; a melded heap forwards to the heap it was melded into
+#[cfg(feature = "std")]
+struct PairingHeapOwner<'a> {
+    melded_into: Cell<Option<&'a PairingHeapOwner<'a>>>,
+}
+
+/// refers to an entry pushed into a `PairingHeap`, valid in that heap or in the heap it is melded into.
+/// Other heaps ignore it.
+#[cfg(feature = "std")]
+pub struct PairingHeapHandle<'a, T, S>(&'a PairingHeapNode<'a, T, S>);
+
+#[cfg(feature = "std")]
+impl<T, S> Clone for PairingHeapHandle<'_, T, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S> Copy for PairingHeapHandle<'_, T, S> {}
+
+#[cfg(feature = "std")]
+impl<'a, T, S: PartialOrd> PairingHeapNode<'a, T, S> {
+    fn priority(&self) -> &S {
+        unsafe { &*self.priority.get() }
+    }
+
+    // makes the root with the larger priority the leftmost child of the other one
+    fn link(a: &'a Self, b: &'a Self) -> &'a Self {
+        let (parent, child) = if b.priority() < a.priority() { (b, a) } else { (a, b) };
+        child.sibling.set(parent.child.get());
+        if let Some(next) = parent.child.get() {
+            next.prev.set(Some(child));
+        }
+        child.prev.set(Some(parent));
+        parent.child.set(Some(child));
+        parent
+    }
+
+    // two pass pairing of a sibling list: link pairs from left to right, then link the results from right to left
+    fn merge_pairs(first: Option<&'a Self>) -> Option<&'a Self> {
+        let (mut stack, mut next) = (None, first);
+        while let Some(a) = next {
+            let linked = match a.sibling.get() {
+                Some(b) => {
+                    next = b.sibling.get();
+                    for node in [a, b] {
+                        node.sibling.set(None);
+                        node.prev.set(None);
+                    }
+                    Self::link(a, b)
+                }
+                None => {
+                    next = None;
+                    a.prev.set(None);
+                    a
+                }
+            };
+            linked.sibling.set(stack); // the stack of linked pairs reuses the sibling pointers
+            stack = Some(linked);
+        }
+
+        let mut root: Option<&'a Self> = None;
+        while let Some(node) = stack {
+            stack = node.sibling.get();
+            node.sibling.set(None);
+            root = Some(match root {
+                Some(root) => Self::link(root, node),
+                None => node,
+            });
+        }
+        root
+    }
+
+    // removes the subtree rooted at a non-root node from its parent
+    fn detach(&self) {
+        let (prev, sibling) = (self.prev.get().unwrap(), self.sibling.get());
+        if prev.child.get().is_some_and(|child| core::ptr::eq(child, self)) {
+            prev.child.set(sibling);
+        } else {
+            prev.sibling.set(sibling);
+        }
+        if let Some(sibling) = sibling {
+            sibling.prev.set(Some(prev));
+        }
+        self.prev.set(None);
+        self.sibling.set(None);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T, S: PartialOrd, const N: usize, const C: usize> PairingHeap<'a, T, S, N, C> {
+    pub fn new(arena: &'a Arena<N, C>) -> Self {
+        let owner = arena.alloc(PairingHeapOwner { melded_into: Cell::new(None) });
+        Self { arena, owner, root: None, len: 0 }
+    }
+
+    pub fn push(&mut self, data: T, priority: S) -> PairingHeapHandle<'a, T, S> {
+        let node: &'a PairingHeapNode<'a, T, S> = self.arena.alloc(PairingHeapNode {
+            data: ManuallyDrop::new(data),
+            priority: UnsafeCell::new(priority),
+            alive: Cell::new(true),
+            owner: Cell::new(self.owner),
+            child: Cell::new(None),
+            sibling: Cell::new(None),
+            prev: Cell::new(None),
+        });
+        self.root = Some(match self.root {
+            Some(root) => PairingHeapNode::link(root, node),
+            None => node,
+        });
+        self.len += 1;
+        PairingHeapHandle(node)
+    }
+
+    /// move all entries of `other`, which must use the same arena, into this heap in O(1). Handles of `other` stay valid.
+    pub fn meld(&mut self, mut other: Self) {
+        assert!(core::ptr::eq(self.arena, other.arena), "PairingHeap can only meld heaps in the same arena");
+        other.owner.melded_into.set(Some(self.owner));
+        if let Some(other_root) = other.root.take() {
+            self.root = Some(match self.root {
+                Some(root) => PairingHeapNode::link(root, other_root),
+                None => other_root,
+            });
+        }
+        self.len += core::mem::take(&mut other.len);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.pop_with_priority().map(|(data, _)| data)
+    }
+
+    pub fn pop_with_priority(&mut self) -> Option<(T, S)> {
+        let root = self.root?;
+        self.root = PairingHeapNode::merge_pairs(root.child.take());
+        self.len -= 1;
+        root.alive.set(false);
+        // the node is marked dead and never read again
+        unsafe { Some((core::ptr::read(&*root.data), core::ptr::read(root.priority.get()))) }
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.root.map(|root| &*root.data)
+    }
+
+    pub fn peek_with_priority(&self) -> Option<(&T, &S)> {
+        self.root.map(|root| (&*root.data, root.priority()))
+    }
+
+    /// lower the priority of the entry. Return false if the new priority is not smaller, the entry has been popped,
+    /// or it belongs to another heap.
+    pub fn decrease_priority(&mut self, handle: PairingHeapHandle<'a, T, S>, priority: S) -> bool {
+        let node = handle.0;
+        if !self.owns(node) || priority.partial_cmp(node.priority()) != Some(core::cmp::Ordering::Less) {
+            return false
+        }
+
+        unsafe { *node.priority.get() = priority };
+        if node.prev.get().is_some() {
+            node.detach();
+            self.root = Some(PairingHeapNode::link(self.root.unwrap(), node));
+        }
+        true
+    }
+
+    /// the data of the entry, or None if it has been popped or belongs to another heap
+    pub fn get(&self, handle: PairingHeapHandle<'a, T, S>) -> Option<&T> {
+        self.owns(handle.0).then(|| &*handle.0.data)
+    }
+
+    pub fn priority_of(&self, handle: PairingHeapHandle<'a, T, S>) -> Option<&S> {
+        self.owns(handle.0).then(|| handle.0.priority())
+    }
+
+    // whether the node is alive in this heap, following the melds and shortening the chain for the next lookup
+    fn owns(&self, node: &PairingHeapNode<'a, T, S>) -> bool {
+        let mut owner = node.owner.get();
+        while let Some(next) = owner.melded_into.get() {
+            owner = next;
+        }
+        node.owner.set(owner);
+        node.alive.get() && core::ptr::eq(owner, self.owner)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.iter_with_priority().map(|(data, _)| data)
+    }
+
+    pub fn iter_with_priority(&self) -> impl Iterator<Item = (&T, &S)> {
+        let mut stack: Vec<&PairingHeapNode<'a, T, S>> = self.root.into_iter().collect();
+        core::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.child.get());
+            stack.extend(node.sibling.get());
+            Some((&*node.data, node.priority()))
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd, const N: usize, const C: usize> Extend<(T, S)> for PairingHeap<'_, T, S, N, C> {
+    fn extend<I: IntoIterator<Item = (T, S)>>(&mut self, iter: I) {
+        for (data, priority) in iter {
+            self.push(data, priority);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd, const N: usize, const C: usize> Drop for PairingHeap<'_, T, S, N, C> {
+    fn drop(&mut self) { // the arena does not run drop glue
+        self.clear()
+    }
+}
+
+/// a double-ended priority queue (Atkinson et al., 1986) with O(1) access to both the minimum and the maximum.
+/// Nodes on even levels are smaller than their descendants and nodes on odd levels are larger.
+/// `push_bounded` keeps at most `capacity` entries by evicting the maximum, e.g. for the frontier of a beam search.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct MinMaxHeap<T, S: PartialOrd = f64>(Vec<(T, S)>);
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd> MinMaxHeap<T, S> {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+
+    pub fn push(&mut self, data: T, priority: S) {
+        self.0.push((data, priority));
+        self.bubble_up(self.0.len() - 1);
+    }
+
+    /// push the entry, then pop the maximum if there are more than `capacity` entries. Return the evicted entry,
+    /// which is the new one if its priority is not smaller than the current maximum.
+    pub fn push_bounded(&mut self, data: T, priority: S, capacity: usize) -> Option<(T, S)> {
+        if self.0.len() < capacity {
+            self.push(data, priority);
+            return None
+        }
+        match self.peek_max_with_priority() {
+            Some((_, max)) if priority < *max => {
+                let evicted = self.pop_max_with_priority();
+                self.push(data, priority);
+                evicted
+            }
+            _ => Some((data, priority))
+        }
+    }
+
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.pop_min_with_priority().map(|(data, _)| data)
+    }
+
+    pub fn pop_min_with_priority(&mut self) -> Option<(T, S)> {
+        self.remove(0)
+    }
+
+    pub fn pop_max(&mut self) -> Option<T> {
+        self.pop_max_with_priority().map(|(data, _)| data)
+    }
+
+    pub fn pop_max_with_priority(&mut self) -> Option<(T, S)> {
+        self.remove(self.max_index()?)
+    }
+
+    pub fn peek_min(&self) -> Option<&T> {
+        self.peek_min_with_priority().map(|(data, _)| data)
+    }
+
+    pub fn peek_min_with_priority(&self) -> Option<(&T, &S)> {
+        self.0.first().map(|(data, priority)| (data, priority))
+    }
+
+    pub fn peek_max(&self) -> Option<&T> {
+        self.peek_max_with_priority().map(|(data, _)| data)
+    }
+
+    pub fn peek_max_with_priority(&self) -> Option<(&T, &S)> {
+        self.max_index().map(|i| (&self.0[i].0, &self.0[i].1))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.0.iter().map(|(data, _)| data)
+    }
+
+    pub fn iter_with_priority(&self) -> impl Iterator<Item = (&T, &S)> {
+        self.0.iter().map(|(data, priority)| (data, priority))
+    }
+
+    fn max_index(&self) -> Option<usize> {
+        match self.0.len() {
+            0 => None,
+            1 => Some(0),
+            2 => Some(1),
+            _ => Some(if self.0[2].1 > self.0[1].1 { 2 } else { 1 }),
+        }
+    }
+
+    fn remove(&mut self, i: usize) -> Option<(T, S)> {
+        if i >= self.0.len() {
+            return None
+        }
+        let entry = self.0.swap_remove(i);
+        if i < self.0.len() {
+            self.trickle_down(i);
+        }
+        Some(entry)
+    }
+
+    fn is_min_level(i: usize) -> bool {
+        (i + 1).ilog2().is_multiple_of(2)
+    }
+
+    // `before(a, b)` is `a < b` on min levels and `a > b` on max levels
+    fn before(&self, a: usize, b: usize, min_level: bool) -> bool {
+        if min_level { self.0[a].1 < self.0[b].1 } else { self.0[a].1 > self.0[b].1 }
+    }
+
+    fn bubble_up(&mut self, i: usize) {
+        if i == 0 {
+            return
+        }
+        let parent = (i - 1) / 2;
+        let min_level = Self::is_min_level(i);
+        if self.before(parent, i, min_level) {
+            self.0.swap(i, parent);
+            self.bubble_up_grandparents(parent, !min_level);
+        } else {
+            self.bubble_up_grandparents(i, min_level);
+        }
+    }
+
+    fn bubble_up_grandparents(&mut self, mut i: usize, min_level: bool) {
+        while i > 2 {
+            let grandparent = ((i - 1) / 2 - 1) / 2;
+            if !self.before(i, grandparent, min_level) {
+                return
+            }
+            self.0.swap(i, grandparent);
+            i = grandparent;
+        }
+    }
+
+    fn trickle_down(&mut self, mut i: usize) {
+        let min_level = Self::is_min_level(i);
+        loop {
+            let descendants = [2 * i + 1, 2 * i + 2, 4 * i + 3, 4 * i + 4, 4 * i + 5, 4 * i + 6];
+            let Some(m) = descendants.into_iter()
+                .filter(|&j| j < self.0.len())
+                .reduce(|m, j| if self.before(j, m, min_level) { j } else { m })
+            else { return };
+
+            if !self.before(m, i, min_level) {
+                return
+            }
+            self.0.swap(m, i);
+            if m <= 2 * i + 2 { // a child, which is on the other kind of level and has no further descendants to fix
+                return
+            }
+            let parent = (m - 1) / 2;
+            if self.before(parent, m, min_level) {
+                self.0.swap(m, parent);
+            }
+            i = m;
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd> FromIterator<(T, S)> for MinMaxHeap<T, S> {
+    fn from_iter<I: IntoIterator<Item = (T, S)>>(iter: I) -> Self {
+        let mut heap = Self::new();
+        heap.extend(iter);
+        heap
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd> Extend<(T, S)> for MinMaxHeap<T, S> {
+    fn extend<I: IntoIterator<Item = (T, S)>>(&mut self, iter: I) {
+        for (data, priority) in iter {
+            self.push(data, priority);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd> IntoIterator for MinMaxHeap<T, S> {
+    type Item = (T, S);
+    type IntoIter = std::vec::IntoIter<(T, S)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S: PartialOrd> Default for MinMaxHeap<T, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 #[cfg(feature = "std")]
 #[cfg(test)]
@@ -141,4 +1082,223 @@ mod tests{
         heap.clear();
         assert!(heap.is_empty());
     }
+
+    #[test]
+    fn keyed_min_heap() {
+        fn foo<C: MapConstructor<&'static str>>() {
+            let mut heap: KeyedMinHeap<_, _, C> = [("a", 4), ("b", 2), ("c", 3), ("d", 7), ("e", 5)].into_iter().collect();
+            assert_eq!(heap.peek_with_priority(), Some((&"b", &2)));
+
+            assert!(heap.push_or_decrease("d", 1));
+            assert!(!heap.push_or_decrease("a", 6));
+            assert!(heap.push_or_decrease("f", 8));
+            assert_eq!(heap.priority_of(&"a"), Some(&4));
+            assert_eq!(heap.peek(), Some(&"d"));
+
+            assert_eq!(heap.change_priority(&"d", 9), Some(1));
+            assert_eq!(heap.change_priority(&"z", 0), None);
+            assert_eq!(heap.push("c", 0), Some(3));
+            assert_eq!(heap.remove(&"b"), Some(2));
+            assert_eq!(heap.remove(&"b"), None);
+            assert!(!heap.contains(&"b") && heap.contains(&"e"));
+            assert_eq!(heap.len(), 5);
+
+            let mut order = vec![];
+            while let Some(entry) = heap.pop_with_priority() {
+                order.push(entry);
+            }
+            assert_eq!(order, [("c", 0), ("a", 4), ("e", 5), ("f", 8), ("d", 9)]);
+            assert!(heap.is_empty() && heap.priority_of(&"a").is_none());
+        }
+
+        foo::<HashMapConstructor>();
+        foo::<crate::BTreeMapConstructor>();
+    }
+
+    #[test]
+    fn radix_heap() {
+        let mut rng = crate::Xorshift32::new(39393);
+        let mut heap = RadixHeap::new();
+        let mut reference = MinHeap::new();
+        let mut last = 0u32;
+        for round in 0..2000 {
+            for _ in 0..rng.gen_u32() % 3 {
+                let priority = last + rng.gen_u32() % 100;
+                heap.push(round, priority);
+                reference.push(round, priority);
+            }
+            assert_eq!(heap.len(), reference.len());
+            assert_eq!(heap.peek_with_priority().map(|(_, p)| *p), reference.peek_with_priority().map(|(_, p)| *p));
+            if let Some((_, priority)) = heap.pop_with_priority() {
+                assert_eq!(Some(priority), reference.pop_with_priority().map(|(_, p)| p));
+                last = priority;
+            }
+        }
+
+        let mut heap: RadixHeap<_, i64> = RadixHeap::with_min(-10);
+        heap.extend([("a", 3), ("b", -10), ("c", -1), ("d", 3)]);
+        assert_eq!(heap.iter().count(), 4);
+        assert_eq!(heap.pop_with_priority(), Some(("b", -10)));
+        assert_eq!(heap.pop(), Some("c"));
+        heap.push("e", 0);
+        assert_eq!(heap.pop(), Some("e"));
+        assert_eq!(heap.into_iter().map(|(_, p)| p).collect::<Vec<_>>(), [3, 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn radix_heap_non_monotone() {
+        let mut heap = RadixHeap::new();
+        heap.push("a", 5u8);
+        heap.pop();
+        heap.push("b", 4);
+    }
+
+    #[test]
+    fn d_ary_min_heap() {
+        fn foo<const D: usize>() {
+            let mut rng = crate::Xorshift32::new(39393);
+            let mut heap: DAryMinHeap<_, _, D> = (0..500).map(|i| (i, rng.gen_u32() % 1000)).collect();
+            heap.push(500, 0);
+            assert_eq!(heap.peek_with_priority(), Some((&500, &0)));
+            assert_eq!(heap.len(), 501);
+
+            let mut priorities = vec![];
+            while let Some((_, priority)) = heap.pop_with_priority() {
+                priorities.push(priority);
+            }
+            assert!(priorities.windows(2).all(|w| w[0] <= w[1]));
+            assert!(heap.is_empty() && heap.pop().is_none());
+        }
+
+        foo::<2>();
+        foo::<3>();
+        foo::<4>();
+        foo::<8>();
+
+        let mut heap: DAryMinHeap<_> = DAryMinHeap::new();
+        heap.push("a", 0.5);
+        heap.push("b", 0.25);
+        assert_eq!(heap.pop(), Some("b"));
+    }
+
+    #[test]
+    fn pairing_heap() {
+        let arena: Arena = Arena::new();
+        let mut rng = crate::Xorshift32::new(39393);
+        let mut heap = PairingHeap::new(&arena);
+        let handles: Vec<_> = (0..300).map(|i| heap.push(i, rng.gen_u32() % 1000)).collect();
+        assert_eq!(heap.len(), 300);
+
+        for handle in handles.iter().step_by(7) {
+            let priority = *heap.priority_of(*handle).unwrap();
+            assert!(heap.decrease_priority(*handle, priority / 2));
+            assert!(!heap.decrease_priority(*handle, priority));
+        }
+        assert!(heap.decrease_priority(handles[150], 0));
+        assert_eq!(heap.peek_with_priority(), Some((&150, &0)));
+        assert_eq!(heap.iter().count(), 300);
+
+        let mut other = PairingHeap::new(&arena);
+        other.extend((300..400).map(|i| (i, rng.gen_u32() % 1000)));
+        let other_handle = other.push(400, 2000);
+        heap.meld(other);
+        assert_eq!(heap.len(), 401);
+        assert!(heap.decrease_priority(other_handle, 1));
+
+        let mut popped = vec![];
+        while let Some((data, priority)) = heap.pop_with_priority() {
+            popped.push((data, priority));
+        }
+        assert_eq!(popped.len(), 401);
+        assert_eq!(&popped[..2], &[(150, 0), (400, 1)]);
+        assert!(popped.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert_eq!(heap.get(handles[0]), None);
+        assert!(!heap.decrease_priority(handles[0], 0));
+
+        let (mut a, mut b) = (PairingHeap::new(&arena), PairingHeap::new(&arena));
+        let (in_a, in_b) = (a.push('a', 5), b.push('b', 5));
+        assert_eq!((b.get(in_a), b.priority_of(in_a)), (None, None));
+        assert!(!b.decrease_priority(in_a, 0) && !a.decrease_priority(in_b, 0));
+        assert_eq!((a.peek(), b.peek()), (Some(&'a'), Some(&'b')));
+        b.meld(a);
+        assert!(b.decrease_priority(in_a, 0));
+        assert_eq!(b.pop_with_priority(), Some(('a', 0)));
+    }
+
+    #[test]
+    fn pairing_heap_drops_data() {
+        let counter = std::rc::Rc::new(());
+        let arena: Arena = Arena::new();
+        {
+            let mut heap = PairingHeap::new(&arena);
+            for i in 0..10 {
+                heap.push(counter.clone(), i);
+            }
+            heap.pop();
+            assert_eq!(std::rc::Rc::strong_count(&counter), 10);
+        }
+        assert_eq!(std::rc::Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn min_max_heap() {
+        let mut rng = crate::Xorshift32::new(39393);
+        let mut heap = MinMaxHeap::new();
+        let mut reference = vec![];
+        for i in 0..3000 {
+            match rng.gen_u32() % 4 {
+                0 => assert_eq!(heap.pop_min_with_priority().map(|(_, p)| p), reference.first().copied().inspect(|_| { reference.remove(0); })),
+                1 => assert_eq!(heap.pop_max_with_priority().map(|(_, p)| p), reference.pop()),
+                _ => {
+                    let priority = rng.gen_u32() % 500;
+                    heap.push(i, priority);
+                    let position = reference.partition_point(|&p| p < priority);
+                    reference.insert(position, priority);
+                }
+            }
+            assert_eq!(heap.len(), reference.len());
+            assert_eq!(heap.peek_min_with_priority().map(|(_, p)| *p), reference.first().copied());
+            assert_eq!(heap.peek_max_with_priority().map(|(_, p)| *p), reference.last().copied());
+        }
+    }
+
+    #[test]
+    fn min_max_heap_bounded() {
+        let mut beam = MinMaxHeap::new();
+        let mut evicted = vec![];
+        for (data, priority) in [("a", 5.), ("b", 1.), ("c", 3.), ("d", 4.), ("e", 9.), ("f", 2.)] {
+            evicted.extend(beam.push_bounded(data, priority, 3));
+        }
+        assert_eq!(evicted, [("a", 5.), ("e", 9.), ("d", 4.)]);
+        assert_eq!(beam.peek_max(), Some(&"c"));
+        assert_eq!(beam.pop_min(), Some("b"));
+        assert_eq!(beam.pop_max(), Some("c"));
+        assert_eq!(beam.into_iter().collect::<Vec<_>>(), [("f", 2.)]);
+        assert_eq!(MinMaxHeap::new().push_bounded("x", 0, 0), Some(("x", 0)));
+    }
+
+    #[test]
+    fn min_heap_fifo_and_nan() {
+        let mut heap = MinHeap::new_fifo();
+        for (i, priority) in [2, 1, 2, 1, 2, 1].into_iter().enumerate() {
+            heap.push(i, priority);
+        }
+        let order: Vec<_> = core::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!(order, [1, 3, 5, 0, 2, 4]);
+        let mut heap: MinHeap<_, _, u64> = [("x", 1), ("y", 0), ("z", 1)].into_iter().collect();
+        assert_eq!(core::iter::from_fn(|| heap.pop()).collect::<Vec<_>>(), ["y", "x", "z"]);
+        assert_eq!(size_of::<MinHeapEntry<u32, u32, ()>>(), 8);
+
+        let mut heap = MinHeap::new();
+        heap.extend([("a", 1.), ("nan", f64::NAN), ("b", -1.), ("c", f64::INFINITY)]);
+        let order: Vec<_> = core::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!(order, ["b", "a", "c", "nan"]);
+
+        let mut heap = MinHeap::new();
+        heap.extend([("a", TotalOrd(0.)), ("b", TotalOrd(-0.)), ("c", TotalOrd(-f64::NAN)), ("d", TotalOrd(f32::NAN as f64))]);
+        let order: Vec<_> = core::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!(order, ["c", "b", "a", "d"]);
+        assert!(TotalOrd(1f32) < 2f32.into());
+    }
 }
@@ -150,44 +150,81 @@ pub trait Integer:
             d
         }
     }
+    fn count_ones(self) -> u32 {
+        (0..core::mem::size_of::<Self>() * 8).filter(|&i| self & (Self::one() << i) != zero()).count() as _
+    }
+    fn leading_zeros(self) -> u32 {
+        let bits = core::mem::size_of::<Self>() * 8;
+        (0..bits).rev().take_while(|&i| self & (Self::one() << i) == zero()).count() as _
+    }
 }
 
 impl Integer for u8 {
     fn next_multiple_of(self, rhs: Self) -> Self { self.next_multiple_of(rhs) }
     fn div_ceil(self, rhs: Self) -> Self { self.div_ceil(rhs) }
     fn div_floor(self, rhs: Self) -> Self { self / rhs }
+    fn count_ones(self) -> u32 { self.count_ones() }
+    fn leading_zeros(self) -> u32 { self.leading_zeros() }
+}
+impl Integer for i8 {
+    fn count_ones(self) -> u32 { self.count_ones() }
+    fn leading_zeros(self) -> u32 { self.leading_zeros() }
 }
-impl Integer for i8 {}
 impl Integer for u16 {
     fn next_multiple_of(self, rhs: Self) -> Self { self.next_multiple_of(rhs) }
     fn div_ceil(self, rhs: Self) -> Self { self.div_ceil(rhs) }
     fn div_floor(self, rhs: Self) -> Self { self / rhs }
+    fn count_ones(self) -> u32 { self.count_ones() }
+    fn leading_zeros(self) -> u32 { self.leading_zeros() }
+}
+impl Integer for i16 {
+    fn count_ones(self) -> u32 { self.count_ones() }
+    fn leading_zeros(self) -> u32 { self.leading_zeros() }
 }
-impl Integer for i16 {}
 impl Integer for u32 {
     fn next_multiple_of(self, rhs: Self) -> Self { self.next_multiple_of(rhs) }
     fn div_ceil(self, rhs: Self) -> Self { self.div_ceil(rhs) }
     fn div_floor(self, rhs: Self) -> Self { self / rhs }
+    fn count_ones(self) -> u32 { self.count_ones() }
+    fn leading_zeros(self) -> u32 { self.leading_zeros() }
+}
+impl Integer for i32 {
+    fn count_ones(self) -> u32 { self.count_ones() }
+    fn leading_zeros(self) -> u32 { self.leading_zeros() }
 }
-impl Integer for i32 {}
 impl Integer for u64 {
     fn next_multiple_of(self, rhs: Self) -> Self { self.next_multiple_of(rhs) }
     fn div_ceil(self, rhs: Self) -> Self { self.div_ceil(rhs) }
     fn div_floor(self, rhs: Self) -> Self { self / rhs }
+    fn count_ones(self) -> u32 { self.count_ones() }
+    fn leading_zeros(self) -> u32 { self.leading_zeros() }
+}
+impl Integer for i64 {
+    fn count_ones(self) -> u32 { self.count_ones() }
+    fn leading_zeros(self) -> u32 { self.leading_zeros() }
 }
-impl Integer for i64 {}
 impl Integer for u128 {
     fn next_multiple_of(self, rhs: Self) -> Self { self.next_multiple_of(rhs) }
     fn div_ceil(self, rhs: Self) -> Self { self.div_ceil(rhs) }
     fn div_floor(self, rhs: Self) -> Self { self / rhs }
+    fn count_ones(self) -> u32 { self.count_ones() }
+    fn leading_zeros(self) -> u32 { self.leading_zeros() }
+}
+impl Integer for i128 {
+    fn count_ones(self) -> u32 { self.count_ones() }
+    fn leading_zeros(self) -> u32 { self.leading_zeros() }
 }
-impl Integer for i128 {}
 impl Integer for usize {
     fn next_multiple_of(self, rhs: Self) -> Self { self.next_multiple_of(rhs) }
     fn div_ceil(self, rhs: Self) -> Self { self.div_ceil(rhs) }
     fn div_floor(self, rhs: Self) -> Self { self / rhs }
+    fn count_ones(self) -> u32 { self.count_ones() }
+    fn leading_zeros(self) -> u32 { self.leading_zeros() }
+}
+impl Integer for isize {
+    fn count_ones(self) -> u32 { self.count_ones() }
+    fn leading_zeros(self) -> u32 { self.leading_zeros() }
 }
-impl Integer for isize {}
 
 mod tests {
     #[test]
@@ -203,5 +240,7 @@ mod tests {
         assert_eq!(a.next_multiple_of(10), 10);
         assert_eq!(a.div_ceil(3), 4);
         assert_eq!(super::Integer::div_floor(a, 3), 3);
+        assert_eq!(super::Integer::count_ones(a), 2);
+        assert_eq!(super::Integer::leading_zeros(a), 124);
     }
 }
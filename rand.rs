@@ -1,3 +1,8 @@
+use core::{marker::PhantomData, ops::DerefMut};
+use crate::ArrayVec;
+#[cfg(feature = "std")]
+use crate::MinHeap;
+
 pub struct Xorshift32 {
     state: u32,
 }
@@ -34,8 +39,243 @@ impl Xorshift32 {
         let b = self.gen_u32() as usize;
         (a << 32) | b
     }
+
+    /// uniform in [0, 1) with 53 random bits
+    pub fn gen_f64(&mut self) -> f64 {
+        let bits = (self.gen_u32() as u64) << 21 | (self.gen_u32() >> 11) as u64;
+        bits as f64 / (1u64 << 53) as f64
+    }
+}
+
+// uniform in (0, 1], safe to take the logarithm
+#[cfg(feature = "std")]
+fn gen_open01(rng: &mut Xorshift32) -> f64 {
+    1. - rng.gen_f64()
+}
+
+/// Uniformly samples `k` items from a stream of unknown length.
+/// Uses Algorithm L (Li, 1994) under std, which decides in advance how many items to skip and draws O(k log(n / k))
+/// random numbers; otherwise Algorithm R (Vitter, 1985), which draws one per item.
+/// `S` is the storage, either `Vec<T>` or `ArrayVec<T, N>` for no_std.
+#[cfg(feature = "std")]
+pub struct ReservoirSampler<T, S = Vec<T>> {
+    sample: S,
+    k: usize,
+    seen: usize,
+    w: f64,
+    next: usize, // the index of the next item to be included once the reservoir is full
+    phantom: PhantomData<T>,
+}
+
+#[cfg(not(feature = "std"))]
+pub struct ReservoirSampler<T, S> {
+    sample: S,
+    k: usize,
+    seen: usize,
+    phantom: PhantomData<T>,
+}
+
+#[cfg(feature = "std")]
+impl<T> ReservoirSampler<T, Vec<T>> {
+    pub fn new(k: usize) -> Self {
+        Self::with_storage(Vec::with_capacity(k), k)
+    }
+}
+
+impl<T, const N: usize> ReservoirSampler<T, ArrayVec<T, N>> {
+    /// Creates a sampler of `N` items.
+    pub fn new() -> Self {
+        Self::with_storage(ArrayVec::new(), N)
+    }
+}
+
+impl<T, const N: usize> Default for ReservoirSampler<T, ArrayVec<T, N>> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, S: DerefMut<Target = [T]> + Extend<T>> ReservoirSampler<T, S> {
+    fn with_storage(sample: S, k: usize) -> Self {
+        #[cfg(feature = "std")]
+        return Self { sample, k, seen: 0, w: 0., next: 0, phantom: PhantomData };
+        #[cfg(not(feature = "std"))]
+        return Self { sample, k, seen: 0, phantom: PhantomData };
+    }
+
+    #[cfg(feature = "std")]
+    pub fn observe(&mut self, item: T, rng: &mut Xorshift32) {
+        if self.k == 0 {
+            self.seen += 1; // nothing is kept and the skips below would divide by zero
+            return
+        }
+        if self.sample.len() < self.k {
+            self.sample.extend([item]);
+            if self.sample.len() == self.k {
+                self.w = (gen_open01(rng).ln() / self.k as f64).exp();
+                self.next = self.seen + 1;
+                self.jump(rng);
+            }
+        } else if self.seen == self.next {
+            self.sample[rng.gen_usize() % self.k] = item;
+            self.w *= (gen_open01(rng).ln() / self.k as f64).exp();
+            self.next = self.seen + 1;
+            self.jump(rng);
+        }
+        self.seen += 1;
+    }
+
+    // skips a geometrically distributed number of items
+    #[cfg(feature = "std")]
+    fn jump(&mut self, rng: &mut Xorshift32) {
+        let skip = (gen_open01(rng).ln() / (1. - self.w).ln()).floor();
+        self.next = self.next.saturating_add(if skip.is_finite() { skip as usize } else { usize::MAX });
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub fn observe(&mut self, item: T, rng: &mut Xorshift32) {
+        if self.sample.len() < self.k {
+            self.sample.extend([item]);
+        } else {
+            let j = rng.gen_usize() % (self.seen + 1);
+            if j < self.k {
+                self.sample[j] = item;
+            }
+        }
+        self.seen += 1;
+    }
+
+    /// Observes all items of the iterator. Under std the skipped items are passed over without drawing random numbers.
+    pub fn observe_iter(&mut self, iter: impl IntoIterator<Item = T>, rng: &mut Xorshift32) {
+        let mut iter = iter.into_iter();
+        loop {
+            #[cfg(feature = "std")]
+            let item = if self.k > 0 && self.sample.len() == self.k {
+                let skip = self.next - self.seen;
+                let skipped = iter.by_ref().take(skip).count();
+                self.seen += skipped;
+                if skipped < skip {
+                    return
+                }
+                iter.next()
+            } else {
+                iter.next()
+            };
+            #[cfg(not(feature = "std"))]
+            let item = iter.next();
+
+            match item {
+                Some(item) => self.observe(item, rng),
+                None => return
+            }
+        }
+    }
+
+    /// The number of observed items.
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// The current sample in arbitrary order, with `min(k, seen)` items.
+    pub fn sample(&self) -> &[T] {
+        &self.sample
+    }
+
+    pub fn into_sample(self) -> S {
+        self.sample
+    }
+}
+
+/// Samples `k` items from a stream with probabilities proportional to their weights, without replacement.
+/// Each item gets the key u^(1 / weight) and the `k` largest keys are kept (A-Res, Efraimidis and Spirakis, 2006);
+/// once the reservoir is full, the total weight to skip before the next replacement is drawn directly (A-ExpJ),
+/// so only O(k log(n / k)) random numbers are drawn. Keys are kept as ln(u) / weight to avoid underflow.
+#[cfg(feature = "std")]
+pub struct WeightedReservoirSampler<T> {
+    heap: MinHeap<T, f64>,
+    k: usize,
+    jump: f64, // the remaining weight to skip
+}
+
+#[cfg(feature = "std")]
+impl<T> WeightedReservoirSampler<T> {
+    pub fn new(k: usize) -> Self {
+        Self { heap: MinHeap::with_capacity(k + 1), k, jump: 0. }
+    }
+
+    /// Items with non-positive weights are never sampled.
+    pub fn observe(&mut self, item: T, weight: f64, rng: &mut Xorshift32) {
+        if weight <= 0. || self.k == 0 {
+            return
+        }
+
+        if self.heap.len() < self.k {
+            self.heap.push(item, gen_open01(rng).ln() / weight);
+            if self.heap.len() == self.k {
+                self.jump(rng);
+            }
+            return
+        }
+
+        self.jump -= weight;
+        if self.jump <= 0. {
+            // the key of the new item is drawn conditioned on it exceeding the current minimum
+            let threshold = (weight * self.min_key()).exp();
+            let key = (threshold + rng.gen_f64() * (1. - threshold)).ln() / weight;
+            self.heap.pop();
+            self.heap.push(item, key);
+            self.jump(rng);
+        }
+    }
+
+    fn min_key(&self) -> f64 {
+        *self.heap.peek_with_priority().unwrap().1
+    }
+
+    fn jump(&mut self, rng: &mut Xorshift32) {
+        self.jump = gen_open01(rng).ln() / self.min_key();
+    }
+
+    /// The current sample in arbitrary order.
+    pub fn sample(&self) -> impl Iterator<Item = &T> {
+        self.heap.iter()
+    }
+
+    pub fn into_sample(self) -> Vec<T> {
+        self.heap.into_iter().map(|(item, _)| item).collect()
+    }
 }
 
+pub trait ExtForSample: Iterator + Sized {
+    /// uniformly sample `k` items in arbitrary order, or all items if there are fewer
+    #[cfg(feature = "std")]
+    fn sample_k(self, k: usize, rng: &mut Xorshift32) -> Vec<Self::Item> {
+        let mut sampler = ReservoirSampler::<_>::new(k);
+        sampler.observe_iter(self, rng);
+        sampler.into_sample()
+    }
+
+    /// uniformly sample `N` items in arbitrary order, or all items if there are fewer
+    fn sample_array<const N: usize>(self, rng: &mut Xorshift32) -> ArrayVec<Self::Item, N> {
+        let mut sampler = ReservoirSampler::<_, ArrayVec<_, N>>::new();
+        sampler.observe_iter(self, rng);
+        sampler.into_sample()
+    }
+
+    /// sample `k` items without replacement with probabilities proportional to `weight`
+    #[cfg(feature = "std")]
+    fn sample_weighted_k(self, k: usize, rng: &mut Xorshift32, mut weight: impl FnMut(&Self::Item) -> f64) -> Vec<Self::Item> {
+        let mut sampler = WeightedReservoirSampler::new(k);
+        for item in self {
+            let w = weight(&item);
+            sampler.observe(item, w, rng);
+        }
+        sampler.into_sample()
+    }
+}
+
+impl<I: Iterator> ExtForSample for I {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -54,6 +294,74 @@ mod tests {
         assert!(x > u32::MAX as usize);
     }
 
+    #[test]
+    fn test_xorshift32_f64() {
+        let mut rng = Xorshift32::new(39393);
+        let xs: ArrayVec<f64, 1000> = (0..1000).map(|_| rng.gen_f64()).collect();
+        assert!(xs.iter().all(|&x| (0. ..1.).contains(&x)));
+        assert!((xs.iter().sum::<f64>() / 1000. - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_reservoir_sampling() {
+        let mut rng = Xorshift32::new(39393);
+        let mut counts = [0; 20];
+        for _ in 0..10000 {
+            for i in (0..20).sample_array::<4>(&mut rng) {
+                counts[i] += 1;
+            }
+        }
+        assert!(counts.iter().all(|&n| (1800..2200).contains(&n)), "{counts:?}");
+
+        let mut sampler = ReservoirSampler::<_, ArrayVec<_, 8>>::new();
+        sampler.observe_iter(0..5, &mut rng);
+        assert_eq!(sampler.sample(), &[0, 1, 2, 3, 4]);
+        sampler.observe_iter(5..1000, &mut rng);
+        assert_eq!((sampler.sample().len(), sampler.seen()), (8, 1000));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_reservoir_sampling_std() {
+        let mut rng = Xorshift32::new(39393);
+        let mut counts = vec![0; 1000];
+        for _ in 0..2000 {
+            let sample = (0..1000).sample_k(10, &mut rng);
+            assert_eq!(sample.len(), 10);
+            sample.into_iter().for_each(|i| counts[i] += 1);
+        }
+        let halves = (counts[..500].iter().sum::<usize>(), counts[500..].iter().sum::<usize>());
+        assert!(halves.0.abs_diff(halves.1) < 800, "{halves:?}");
+        assert!(counts[..10].iter().sum::<usize>() < 400);
+        assert_eq!((0..3).sample_k(10, &mut rng).len(), 3);
+        assert!((0..100).sample_k(0, &mut rng).is_empty());
+
+        let mut sampler = ReservoirSampler::<_>::new(5);
+        (0..100).for_each(|i| sampler.observe(i, &mut rng));
+        assert_eq!((sampler.sample().len(), sampler.seen()), (5, 100));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_weighted_reservoir_sampling() {
+        let mut rng = Xorshift32::new(39393);
+        let mut heavy = 0;
+        for _ in 0..10000 {
+            let sample = [("light", 1.), ("heavy", 9.), ("never", 0.)].into_iter().sample_weighted_k(1, &mut rng, |x| x.1);
+            heavy += (sample[0].0 == "heavy") as usize;
+        }
+        assert!((8800..9200).contains(&heavy), "{heavy}");
+
+        // the first item has half of the total weight
+        let mut first = 0;
+        for _ in 0..4000 {
+            let sample = (0..101).sample_weighted_k(2, &mut rng, |&i| if i == 0 { 100. } else { 1. });
+            assert_eq!(sample.len(), 2);
+            first += sample.contains(&0) as usize;
+        }
+        assert!(first > 3000, "{first}");
+    }
+
     #[cfg(feature = "std")]
     #[test]
     fn test_xorshift32_with_system_timestamp() {
@@ -9,122 +9,746 @@ use crate::Arithmetic;
 use crate::Arena;
 #[cfg(feature = "std")]
 use crate::MinHeap;
+#[cfg(feature = "std")]
+use core::cell::Cell;
+#[cfg(feature = "std")]
+use core::mem::ManuallyDrop;
 
+/// best-first search for the cheapest path from any of the initial nodes to a goal node (Dijkstra, or A* with `use_heuristic`).
+/// `eval_node` returns the successors of a node with the costs of the edges, as any `IntoIterator<Item = (Node, S)>`.
+/// By default a node is a goal when `eval_node` returns `None`; with `use_goal`, the goal predicate decides instead,
+/// and `None` is a dead end like an empty list of successors.
+/// With `use_weight` or `use_anytime`, nodes are ordered by cost plus weighted heuristic (Weighted A*),
+/// and `solutions` streams improving solutions with Anytime Repairing A*.
+/// Equal nodes are deduplicated by `P`, the nodes themselves by default or a projection with `use_key`,
+/// and a generated node is dropped before allocation if an equal one is already known with a cost at most as high.
+/// The searches take `&mut self`, where they used to take `&self`, as the closures are `FnMut` and the statistics are
+/// recorded; a shared `ShortestPath` can no longer be solved.
 #[cfg(feature = "std")]
-pub struct ShortestPath<Node, F, H, C, S> where
-    F: Fn(&Node) -> Option<Vec<(Node, S)>>, // F is the evaluation function
-    H: Fn(&Node) -> S, // H is the heuristic function
-    C: for <'a> MapConstructor<&'a Node>, // C is the cache implemenation
-    S: Arithmetic + Clone // S is the score (cost) type
+pub struct ShortestPath<Node, F, H, C, S, G = fn(&Node) -> bool, P = NodeKey> where
+    H: FnMut(&Node) -> S, // H is the heuristic function
+    S: Arithmetic + Clone, // S is the score (cost) type
+    G: FnMut(&Node) -> bool, // G is the goal predicate
 {
-    eval_node: F,
+    eval_node: F, // F is the evaluation function
     heuristic: H,
-    phantom: PhantomData<(Node, C)>,
+    is_goal: Option<G>,
+    key: P,
+    limits: SearchLimits<S>,
+    weights: Vec<S>, // the heuristic weights of the anytime search; `search` uses the last one, 1 if empty
+    stats: SearchStats,
+    phantom: PhantomData<(Node, C)>, // C is the cache implementation, keyed by the keys of `P`
+}
+
+/// identifies nodes by themselves, comparing the nodes in the arena without copying them
+pub struct NodeKey;
+
+#[cfg(feature = "std")]
+use key::SearchKey;
+
+// sealed, so that only `ShortestPath` creates the `NodeRef` keys, whose nodes it keeps alive
+#[cfg(feature = "std")]
+mod key {
+    use core::hash::{Hash, Hasher};
+    use core::cmp::Ordering;
+
+    /// how `ShortestPath` identifies equal nodes: `NodeKey`, or a projection `FnMut(&Node) -> K` with `use_key`
+    pub trait SearchKey<Node> {
+        type Key;
+        fn key(&mut self, node: &Node) -> Self::Key;
+    }
+
+    impl<Node> SearchKey<Node> for super::NodeKey {
+        type Key = NodeRef<Node>;
+        fn key(&mut self, node: &Node) -> NodeRef<Node> {
+            NodeRef(node)
+        }
+    }
+
+    impl<Node, K, P: FnMut(&Node) -> K> SearchKey<Node> for P {
+        type Key = K;
+        fn key(&mut self, node: &Node) -> K {
+            self(node)
+        }
+    }
+
+    /// the key of `NodeKey`, which compares and hashes the node it points to.
+    // the keys in the maps point into the arena, which outlives the maps, and a key looked up points to the generated
+    // node during the lookup only. It cannot be cloned, so a map cannot keep a key it is only given by reference.
+    pub struct NodeRef<Node>(*const Node);
+
+    impl<Node> NodeRef<Node> {
+        fn node(&self) -> &Node {
+            unsafe { &*self.0 }
+        }
+    }
+
+    impl<Node: PartialEq> PartialEq for NodeRef<Node> {
+        fn eq(&self, other: &Self) -> bool {
+            self.node() == other.node()
+        }
+    }
+
+    impl<Node: Eq> Eq for NodeRef<Node> {}
+
+    impl<Node: PartialOrd> PartialOrd for NodeRef<Node> {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            self.node().partial_cmp(other.node())
+        }
+    }
+
+    impl<Node: Ord> Ord for NodeRef<Node> {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.node().cmp(other.node())
+        }
+    }
+
+    impl<Node: Hash> Hash for NodeRef<Node> {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.node().hash(state)
+        }
+    }
 }
 
+/// counters of the last search
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    pub expanded: usize,
+    pub generated: usize,
+    pub duplicates_pruned: usize,
+    pub peak_frontier: usize,
+}
+
+/// the outcome of `ShortestPath::search`
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchResult<Node, S> {
+    /// the cheapest path to a goal and its cost
+    Found(Vec<Node>, S),
+    /// no goal is reachable
+    Exhausted,
+    /// the search stopped early. `best_partial` is the path to the expanded node with the lowest heuristic
+    /// (the costliest one on ties, which is the last expanded without a heuristic) and its cost
+    LimitReached { limit: SearchLimit, best_partial: Option<(Vec<Node>, S)> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchLimit {
+    Expanded,
+    Deadline,
+    /// nodes were pruned because their estimated cost exceeded the bound, and no goal was found within it
+    CostBound,
+}
+
+#[cfg(feature = "std")]
+impl<Node, S> SearchResult<Node, S> {
+    pub fn found(self) -> Option<(Vec<Node>, S)> {
+        match self {
+            SearchResult::Found(path, cost) => Some((path, cost)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+struct SearchLimits<S> {
+    max_expanded: Option<usize>,
+    deadline: Option<std::time::Instant>,
+    cost_bound: Option<S>,
+}
+
+/// a solution of `ShortestPath::solutions`
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnytimeSolution<Node, S> {
+    pub path: Vec<Node>,
+    pub cost: S,
+    /// the weight of the iteration; `cost <= weight * optimal cost` with a consistent heuristic
+    pub weight: S,
+    /// the lowest cost plus heuristic of the nodes left to expand; `lower_bound <= optimal cost` with an admissible heuristic
+    pub lower_bound: S,
+}
+
+#[cfg(feature = "std")]
+struct SearchNode<'a, Node, S> {
+    node: Node,
+    cost: S,
+    estimate: S, // the heuristic of the node
+    parent: Option<&'a SearchNode<'a, Node, S>>,
+    expanded_in: Cell<usize>, // the last iteration that expanded the key of the node, 0 if none
+    superseded: Cell<bool>, // whether a cheaper equal node was generated after this one
+}
+
+#[cfg(feature = "std")]
+impl<Node, S> SearchNode<'_, Node, S> {
+    fn path(&self) -> Vec<&Node> {
+        let mut path = vec![&self.node];
+        let mut current = self;
+        while let Some(parent) = current.parent {
+            path.push(&parent.node);
+            current = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    // moves the nodes of the path out of the arena, which never drops them; each node must be read at most once
+    unsafe fn read_path(&self) -> Vec<Node> {
+        self.path().into_iter().map(|node| unsafe { core::ptr::read(node) }).collect()
+    }
+}
+
+// the cheapest node generated for every key
+#[cfg(feature = "std")]
+type BestMap<'a, Node, S, C, P> = <C as MapConstructor<<P as SearchKey<Node>>::Key>>::Map<&'a SearchNode<'a, Node, S>>;
+
+// the state of a search keyed by `P`
+#[cfg(feature = "std")]
+type KeyedState<'a, Node, S, C, P> = SearchState<'a, Node, S, BestMap<'a, Node, S, C, P>>;
+
+// the forward and backward nodes of the cheapest path found by a bidirectional search
 #[cfg(feature = "std")]
-impl<Node: Eq, F, S> ShortestPath<Node, F, fn(&Node) -> S, ArrayMapConstructor<0>, S> where
-    F: Fn(&Node) -> Option<Vec<(Node, S)>>,
+type Meeting<'a, Node, S> = Option<(&'a SearchNode<'a, Node, S>, &'a SearchNode<'a, Node, S>)>;
+
+#[cfg(feature = "std")]
+struct SearchState<'a, Node, S: PartialOrd, M> {
+    arena: &'a Arena,
+    best: M,
+    frontier: MinHeap<&'a SearchNode<'a, Node, S>, S>, // by cost plus weighted heuristic
+    incons: Option<Vec<&'a SearchNode<'a, Node, S>>>, // ARA* only: nodes improved after their key was expanded in this iteration
+    allocated: Option<Vec<*mut SearchNode<'a, Node, S>>>, // ARA* only: every node, dropped with the `AnytimeSearch`
+    iteration: usize,
+    weight: S,
+    use_heuristic: bool, // false for bidirectional search, whose nodes are ordered by cost only
+    bounded: bool, // whether a node was pruned by the cost bound
+    best_partial: Option<&'a SearchNode<'a, Node, S>>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, Node, S: PartialOrd, M: Default> SearchState<'a, Node, S, M> {
+    fn new(arena: &'a Arena, weight: S, anytime: bool, use_heuristic: bool) -> Self {
+        let (incons, allocated) = match anytime {
+            true => (Some(vec![]), Some(vec![])),
+            false => (None, None),
+        };
+        SearchState { arena, best: M::default(), frontier: MinHeap::new(), incons, allocated, iteration: 1, weight, use_heuristic, bounded: false, best_partial: None }
+    }
+}
+
+#[cfg(feature = "std")]
+enum Improved<'a, Node, S> {
+    Goal(&'a SearchNode<'a, Node, S>),
+    Exhausted,
+    Limit(SearchLimit),
+}
+
+#[cfg(feature = "std")]
+impl<Node: Eq, F, I, S> ShortestPath<Node, F, fn(&Node) -> S, ArrayMapConstructor<0>, S> where
+    F: FnMut(&Node) -> Option<I>,
+    I: IntoIterator<Item = (Node, S)>,
     S: Arithmetic + Clone
 {
     pub fn new(eval_node: F) -> Self {
         ShortestPath {
             eval_node,
             heuristic: |_| S::zero(),
+            is_goal: None,
+            key: NodeKey,
+            limits: SearchLimits { max_expanded: None, deadline: None, cost_bound: None },
+            weights: vec![],
+            stats: SearchStats::default(),
             phantom: PhantomData,
         }
     }
 }
 
 #[cfg(feature = "std")]
-impl<Node: Eq + Hash, F, H, S> ShortestPath<Node, F, H, ArrayMapConstructor<0>, S> where
-    F: Fn(&Node) -> Option<Vec<(Node, S)>>,
-    H: Fn(&Node) -> S,
-    S: Arithmetic + Clone
+impl<Node, F, H, S, G, P> ShortestPath<Node, F, H, ArrayMapConstructor<0>, S, G, P> where
+    H: FnMut(&Node) -> S,
+    S: Arithmetic + Clone,
+    G: FnMut(&Node) -> bool,
+    P: SearchKey<Node>,
 {
-    pub fn use_hash_map(self) -> ShortestPath<Node, F, H, HashMapConstructor, S> {
+    pub fn use_hash_map(self) -> ShortestPath<Node, F, H, HashMapConstructor, S, G, P> where
+        P::Key: Eq + Hash
+    {
         ShortestPath {
             eval_node: self.eval_node,
             heuristic: self.heuristic,
+            is_goal: self.is_goal,
+            key: self.key,
+            limits: self.limits,
+            weights: self.weights,
+            stats: self.stats,
             phantom: PhantomData,
         }
     }
-}
 
-#[cfg(feature = "std")]
-impl<Node: Eq + Ord, F, H, S> ShortestPath<Node, F, H, ArrayMapConstructor<0>, S> where
-    F: Fn(&Node) -> Option<Vec<(Node, S)>>,
-    H: Fn(&Node) -> S,
-    S: Arithmetic + Clone
-{
-    pub fn use_btree_map(self) -> ShortestPath<Node, F, H, BTreeMapConstructor, S> {
+    pub fn use_btree_map(self) -> ShortestPath<Node, F, H, BTreeMapConstructor, S, G, P> where
+        P::Key: Eq + Ord
+    {
         ShortestPath {
             eval_node: self.eval_node,
             heuristic: self.heuristic,
+            is_goal: self.is_goal,
+            key: self.key,
+            limits: self.limits,
+            weights: self.weights,
+            stats: self.stats,
             phantom: PhantomData,
         }
     }
 }
 
 #[cfg(feature = "std")]
-impl<Node, F, H, C, S> ShortestPath<Node, F, H, C, S> where
-    F: Fn(&Node) -> Option<Vec<(Node, S)>>,
-    H: Fn(&Node) -> S,
-    C: for <'a> MapConstructor<&'a Node>,
-    S: Arithmetic + Clone
+impl<Node, F, H, C, S, G, P> ShortestPath<Node, F, H, C, S, G, P> where
+    H: FnMut(&Node) -> S,
+    S: Arithmetic + Clone,
+    G: FnMut(&Node) -> bool,
 {
-    pub fn use_heuristic<H2: Fn(&Node) -> S>(self, heuristic: H2) -> ShortestPath<Node, F, H2, C, S> {
+    pub fn use_heuristic<H2: FnMut(&Node) -> S>(self, heuristic: H2) -> ShortestPath<Node, F, H2, C, S, G, P> {
         ShortestPath {
             eval_node: self.eval_node,
             heuristic,
+            is_goal: self.is_goal,
+            key: self.key,
+            limits: self.limits,
+            weights: self.weights,
+            stats: self.stats,
+            phantom: PhantomData,
+        }
+    }
+
+    /// use a separate goal predicate, so that goal nodes can have successors and `None` from `eval_node` means a dead end
+    pub fn use_goal<G2: FnMut(&Node) -> bool>(self, is_goal: G2) -> ShortestPath<Node, F, H, C, S, G2, P> {
+        ShortestPath {
+            eval_node: self.eval_node,
+            heuristic: self.heuristic,
+            is_goal: Some(is_goal),
+            key: self.key,
+            limits: self.limits,
+            weights: self.weights,
+            stats: self.stats,
+            phantom: PhantomData,
+        }
+    }
+
+    /// deduplicate nodes by a cheap projection instead of the nodes themselves; nodes with equal keys must be equal.
+    /// Call it before choosing the map, which is then keyed by `K`.
+    pub fn use_key<K, P2: FnMut(&Node) -> K>(self, key: P2) -> ShortestPath<Node, F, H, C, S, G, P2> {
+        ShortestPath {
+            eval_node: self.eval_node,
+            heuristic: self.heuristic,
+            is_goal: self.is_goal,
+            key,
+            limits: self.limits,
+            weights: self.weights,
+            stats: self.stats,
             phantom: PhantomData,
         }
     }
 
-    pub fn solve(&self, init_nodes: impl IntoIterator<Item=Node>) -> Option<(Vec<Node>, S)> {
+    /// stop after expanding `max_expanded` nodes
+    pub fn use_max_expanded(mut self, max_expanded: usize) -> Self {
+        self.limits.max_expanded = Some(max_expanded);
+        self
+    }
+
+    /// stop at the deadline; it is checked before every expansion
+    pub fn use_deadline(mut self, deadline: std::time::Instant) -> Self {
+        self.limits.deadline = Some(deadline);
+        self
+    }
+
+    /// ignore the nodes whose cost plus heuristic exceeds the bound
+    pub fn use_cost_bound(mut self, cost_bound: S) -> Self {
+        self.limits.cost_bound = Some(cost_bound);
+        self
+    }
+
+    /// Weighted A*: order the nodes by cost plus `weight` times the heuristic. With a consistent heuristic and
+    /// `weight >= 1`, the cost of the solution is at most `weight` times the optimal cost.
+    /// Fractional weights of integer costs can be emulated by scaling the costs.
+    pub fn use_weight(mut self, weight: S) -> Self {
+        self.weights = vec![weight];
+        self
+    }
+
+    /// Anytime Repairing A*: `solutions` runs one Weighted A* iteration per weight, in decreasing order and usually
+    /// ending with 1, reusing the work of the previous iterations. `search` and `solve` use the last weight only.
+    pub fn use_anytime(mut self, weights: impl IntoIterator<Item = S>) -> Self {
+        self.weights = weights.into_iter().collect();
+        assert!(self.weights.windows(2).all(|w| w[0] >= w[1]));
+        self
+    }
+
+    /// the statistics of the last search
+    pub fn stats(&self) -> SearchStats {
+        self.stats
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Node, F, I, H, C, S, G, P> ShortestPath<Node, F, H, C, S, G, P> where
+    F: FnMut(&Node) -> Option<I>,
+    I: IntoIterator<Item = (Node, S)>,
+    H: FnMut(&Node) -> S,
+    C: MapConstructor<P::Key>,
+    S: Arithmetic + Clone,
+    G: FnMut(&Node) -> bool,
+    P: SearchKey<Node>,
+{
+    pub fn solve(&mut self, init_nodes: impl IntoIterator<Item=Node>) -> Option<(Vec<Node>, S)> {
+        self.search(init_nodes).found()
+    }
+
+    pub fn search(&mut self, init_nodes: impl IntoIterator<Item=Node>) -> SearchResult<Node, S> {
         let arena: Arena = Arena::new();
-        let mut frontier = MinHeap::new();
-        let mut came_from = C::new();
+        let weight = self.weights.last().cloned().unwrap_or_else(S::one);
+        let mut state = self.start(&arena, init_nodes, weight, false);
+        let result = self.improve_path(&mut state);
+        let best_partial = || state.best_partial.map(|node| (unsafe { node.read_path() }, node.cost.clone()));
+        match result {
+            Improved::Goal(goal) => SearchResult::Found(unsafe { goal.read_path() }, goal.cost.clone()),
+            Improved::Limit(limit) => SearchResult::LimitReached { limit, best_partial: best_partial() },
+            Improved::Exhausted if state.bounded => SearchResult::LimitReached { limit: SearchLimit::CostBound, best_partial: best_partial() },
+            Improved::Exhausted => SearchResult::Exhausted,
+        }
+    }
 
+    /// streams improving solutions with Anytime Repairing A* (Likhachev, Gordon and Thrun, 2003), one per weight of
+    /// `use_anytime` (or the single weight of `use_weight`). The stream ends early once a solution is proven optimal,
+    /// or when the search is exhausted or stopped by a limit.
+    pub fn solutions(&mut self, init_nodes: impl IntoIterator<Item=Node>) -> AnytimeSearch<'_, Node, F, H, C, S, G, P> where
+        Node: Clone
+    {
+        let mut weights = match self.weights.is_empty() {
+            true => vec![S::one()],
+            false => self.weights.clone(),
+        }.into_iter();
+        let weight = weights.next().unwrap();
+        let arena = core::ptr::NonNull::from(Box::leak(Box::new(Arena::new())));
+        // SAFETY: see `AnytimeSearch`
+        let state = self.start(unsafe { arena.as_ref() }, init_nodes, weight, true);
+        AnytimeSearch { search: self, state: ManuallyDrop::new(state), weights, stopped_by: None, done: false, arena }
+    }
+
+    fn start<'a>(
+        &mut self,
+        arena: &'a Arena,
+        init_nodes: impl IntoIterator<Item=Node>,
+        weight: S,
+        anytime: bool,
+    ) -> SearchState<'a, Node, S, BestMap<'a, Node, S, C, P>> {
+        self.stats = SearchStats::default();
+        let mut state = SearchState::new(arena, weight, anytime, true);
         for node in init_nodes {
-            let node = &*arena.alloc(node);
-            let ecost = (self.heuristic)(node);
-            frontier.push((node, node, S::zero()), ecost);
+            self.generate(&mut state, node, S::zero(), None);
         }
+        state
+    }
 
-        while let Some((node, parent, total_cost)) = frontier.pop() {
-            if came_from.get(&node).is_some_and(|(_, cost)| *cost < total_cost) {
+    // expands nodes until a goal is popped; the goal is not expanded
+    fn improve_path<'a>(&mut self, state: &mut SearchState<'a, Node, S, BestMap<'a, Node, S, C, P>>) -> Improved<'a, Node, S> {
+        while let Some(current) = state.frontier.pop() {
+            if current.superseded.get() {
                 continue
             }
 
-            came_from.insert(node, (parent, total_cost.clone()));
+            if let Some(limit) = self.limit_reached() {
+                return Improved::Limit(limit)
+            }
+
+            self.stats.expanded += 1;
+            if state.best_partial.is_none_or(|partial| current.estimate < partial.estimate || current.estimate == partial.estimate && current.cost >= partial.cost) {
+                state.best_partial = Some(current);
+            }
 
-            if let Some(children) = (self.eval_node)(node) {
-                for (child, cost) in children {
-                    let child = arena.alloc(child);
-                    let child_cost = total_cost.clone() + cost;
-                    let ecost = child_cost.clone() + (self.heuristic)(node);
-                    frontier.push((child, node, child_cost), ecost);
-                }
-            } else {
-                let mut path = vec![node];
-                let mut current = &node;
-                while let Some((parent, _)) = came_from.get(current) {
-                    if core::ptr::eq(parent, current) {
-                        break
-                    }
-                    path.push(parent);
-                    current = parent
+            let (is_goal, children) = match &mut self.is_goal {
+                Some(is_goal) => match is_goal(&current.node) {
+                    true => (true, None),
+                    false => (false, (self.eval_node)(&current.node)),
+                },
+                None => {
+                    let children = (self.eval_node)(&current.node);
+                    (children.is_none(), children)
                 }
-                let mut results = Vec::with_capacity(path.len());
-                while let Some(node) = path.pop() {
-                    results.push(unsafe { core::ptr::read(node) });
+            };
+
+            if is_goal {
+                return Improved::Goal(current)
+            }
+
+            current.expanded_in.set(state.iteration);
+            for (child, cost) in children.into_iter().flatten() {
+                let child_cost = current.cost.clone() + cost;
+                self.generate(state, child, child_cost, Some(current));
+            }
+        }
+
+        Improved::Exhausted
+    }
+
+    fn limit_reached(&self) -> Option<SearchLimit> {
+        if self.limits.max_expanded.is_some_and(|max_expanded| self.stats.expanded >= max_expanded) {
+            Some(SearchLimit::Expanded)
+        } else if self.limits.deadline.is_some_and(|deadline| std::time::Instant::now() >= deadline) {
+            Some(SearchLimit::Deadline)
+        } else {
+            None
+        }
+    }
+
+    // returns the new node unless it was pruned
+    fn generate<'a>(
+        &mut self,
+        state: &mut SearchState<'a, Node, S, BestMap<'a, Node, S, C, P>>,
+        node: Node,
+        cost: S,
+        parent: Option<&'a SearchNode<'a, Node, S>>,
+    ) -> Option<&'a SearchNode<'a, Node, S>> {
+        self.stats.generated += 1;
+
+        let known = state.best.get(&self.key.key(&node)).copied();
+        if known.is_some_and(|known| known.cost <= cost) {
+            self.stats.duplicates_pruned += 1;
+            return None
+        }
+
+        let estimate = if state.use_heuristic { (self.heuristic)(&node) } else { S::zero() };
+        if self.limits.cost_bound.as_ref().is_some_and(|bound| cost.clone() + estimate.clone() > *bound) {
+            state.bounded = true;
+            return None
+        }
+
+        // a node improved after its key was expanded stays closed for the rest of the iteration, however often it is improved
+        let expanded_in = Cell::new(known.map_or(0, |known| known.expanded_in.get()));
+        let search_node: *mut _ = state.arena.alloc(SearchNode { node, cost, estimate, parent, expanded_in, superseded: Cell::new(false) });
+        if let Some(allocated) = &mut state.allocated {
+            allocated.push(search_node);
+        }
+        let search_node = unsafe { &*search_node };
+        if let Some(known) = known {
+            known.superseded.set(true);
+        }
+        state.best.insert(self.key.key(&search_node.node), search_node);
+        match &mut state.incons {
+            Some(incons) if known.is_some_and(|known| known.expanded_in.get() == state.iteration) => incons.push(search_node),
+            _ => {
+                state.frontier.push(search_node, search_node.cost.clone() + state.weight.clone() * search_node.estimate.clone());
+                self.stats.peak_frontier = self.stats.peak_frontier.max(state.frontier.len());
+            }
+        }
+        Some(search_node)
+    }
+
+    /// bidirectional Dijkstra from the initial nodes to any of the goal nodes. `reverse` returns the predecessors of a node
+    /// with the costs of the edges into it. The two frontiers are expanded in turn until their cheapest costs add up to at
+    /// least the cost of the best path found, which is then optimal. The heuristic, the weights and the goal predicate are
+    /// not used, and `None` from `eval_node` is a dead end. Returns `None` if no goal is reachable or a limit is reached.
+    pub fn solve_bidirectional<R, J>(
+        &mut self,
+        init_nodes: impl IntoIterator<Item=Node>,
+        goal_nodes: impl IntoIterator<Item=Node>,
+        mut reverse: R,
+    ) -> Option<(Vec<Node>, S)> where
+        R: FnMut(&Node) -> J,
+        J: IntoIterator<Item = (Node, S)>,
+    {
+        let arena: Arena = Arena::new();
+        let mut forward = SearchState::new(&arena, S::zero(), false, false);
+        let mut backward = SearchState::new(&arena, S::zero(), false, false);
+        let mut meeting: Meeting<Node, S> = None;
+        self.stats = SearchStats::default();
+
+        for node in init_nodes {
+            self.generate(&mut forward, node, S::zero(), None);
+        }
+        for node in goal_nodes {
+            if let Some(node) = self.generate(&mut backward, node, S::zero(), None) {
+                self.meet(node, &forward, false, &mut meeting);
+            }
+        }
+
+        for turn in 0.. {
+            let (Some((_, forward_min)), Some((_, backward_min))) = (forward.frontier.peek_with_priority(), backward.frontier.peek_with_priority()) else {
+                break
+            };
+            if meeting.is_some_and(|(f, b)| forward_min.clone() + backward_min.clone() >= f.cost.clone() + b.cost.clone()) {
+                break
+            }
+
+            let step = match turn % 2 == 0 {
+                true => self.bidirectional_step(&mut forward, &backward, true, &mut meeting, |search, node| (search.eval_node)(node).into_iter().flatten()),
+                false => self.bidirectional_step(&mut backward, &forward, false, &mut meeting, |_, node| reverse(node)),
+            };
+            step.ok()?;
+        }
+
+        let (f, b) = meeting?;
+        let mut path = unsafe { f.read_path() };
+        let mut backward_path = b.path();
+        backward_path.pop(); // the meeting node, already in the forward path
+        path.extend(backward_path.into_iter().rev().map(|node| unsafe { core::ptr::read(node) }));
+        Some((path, f.cost.clone() + b.cost.clone()))
+    }
+
+    // expands the cheapest node of `this` side and records the paths meeting the `other` side
+    fn bidirectional_step<'a, J: IntoIterator<Item = (Node, S)>>(
+        &mut self,
+        this: &mut SearchState<'a, Node, S, BestMap<'a, Node, S, C, P>>,
+        other: &SearchState<'a, Node, S, BestMap<'a, Node, S, C, P>>,
+        forward: bool,
+        meeting: &mut Meeting<'a, Node, S>,
+        successors: impl FnOnce(&mut Self, &Node) -> J,
+    ) -> Result<(), SearchLimit> {
+        while let Some(current) = this.frontier.pop() {
+            if current.superseded.get() {
+                continue
+            }
+            if let Some(limit) = self.limit_reached() {
+                return Err(limit)
+            }
+
+            self.stats.expanded += 1;
+            for (child, cost) in successors(self, &current.node) {
+                let child_cost = current.cost.clone() + cost;
+                if let Some(child) = self.generate(this, child, child_cost, Some(current)) {
+                    self.meet(child, other, forward, meeting);
                 }
-                return Some((results, total_cost));
             }
+            break
+        }
+        Ok(())
+    }
+
+    fn meet<'a>(
+        &mut self,
+        node: &'a SearchNode<'a, Node, S>,
+        other: &SearchState<'a, Node, S, BestMap<'a, Node, S, C, P>>,
+        forward: bool,
+        meeting: &mut Meeting<'a, Node, S>,
+    ) {
+        if let Some(&other_node) = other.best.get(&self.key.key(&node.node)) {
+            let (f, b) = if forward { (node, other_node) } else { (other_node, node) };
+            if meeting.is_none_or(|(mf, mb)| f.cost.clone() + b.cost.clone() < mf.cost.clone() + mb.cost.clone()) {
+                *meeting = Some((f, b));
+            }
+        }
+    }
+}
+
+/// the stream of improving solutions of `ShortestPath::solutions`. The nodes are kept until the stream is dropped.
+// SAFETY: `state` and its nodes live in `arena`, which the stream owns. `'s` stands in for the lifetime of the arena,
+// as no reference to a node leaves the stream, and `drop` drops the state and the nodes before freeing the arena.
+#[cfg(feature = "std")]
+pub struct AnytimeSearch<'s, Node, F, H, C, S, G, P> where
+    H: FnMut(&Node) -> S,
+    C: MapConstructor<P::Key>,
+    S: Arithmetic + Clone,
+    G: FnMut(&Node) -> bool,
+    P: SearchKey<Node>,
+{
+    search: &'s mut ShortestPath<Node, F, H, C, S, G, P>,
+    state: ManuallyDrop<KeyedState<'s, Node, S, C, P>>,
+    weights: std::vec::IntoIter<S>,
+    stopped_by: Option<SearchLimit>,
+    done: bool,
+    arena: core::ptr::NonNull<Arena>, // a raw pointer, so that moving the stream does not invalidate the nodes
+}
+
+#[cfg(feature = "std")]
+impl<Node, F, H, C, S, G, P> Drop for AnytimeSearch<'_, Node, F, H, C, S, G, P> where
+    H: FnMut(&Node) -> S,
+    C: MapConstructor<P::Key>,
+    S: Arithmetic + Clone,
+    G: FnMut(&Node) -> bool,
+    P: SearchKey<Node>,
+{
+    fn drop(&mut self) { // the arena does not run drop glue
+        let allocated = self.state.allocated.take().unwrap_or_default();
+        unsafe {
+            ManuallyDrop::drop(&mut self.state); // the keys of the map may still compare the nodes
+            for node in allocated {
+                core::ptr::drop_in_place(node);
+            }
+            drop(Box::from_raw(self.arena.as_ptr()));
         }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Node, F, H, C, S, G, P> AnytimeSearch<'_, Node, F, H, C, S, G, P> where
+    H: FnMut(&Node) -> S,
+    C: MapConstructor<P::Key>,
+    S: Arithmetic + Clone,
+    G: FnMut(&Node) -> bool,
+    P: SearchKey<Node>,
+{
+    /// the limit that ended the stream, if any
+    pub fn stopped_by(&self) -> Option<SearchLimit> {
+        self.stopped_by
+    }
 
-        None
+    /// the statistics of all iterations so far
+    pub fn stats(&self) -> SearchStats {
+        self.search.stats
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Node, F, I, H, C, S, G, P> Iterator for AnytimeSearch<'_, Node, F, H, C, S, G, P> where
+    Node: Clone,
+    F: FnMut(&Node) -> Option<I>,
+    I: IntoIterator<Item = (Node, S)>,
+    H: FnMut(&Node) -> S,
+    C: MapConstructor<P::Key>,
+    S: Arithmetic + Clone,
+    G: FnMut(&Node) -> bool,
+    P: SearchKey<Node>,
+{
+    type Item = AnytimeSolution<Node, S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None
+        }
+
+        let state = &mut *self.state;
+        let goal = match self.search.improve_path(state) {
+            Improved::Goal(goal) => goal,
+            Improved::Limit(limit) => {
+                (self.stopped_by, self.done) = (Some(limit), true);
+                return None
+            }
+            Improved::Exhausted => {
+                (self.stopped_by, self.done) = (state.bounded.then_some(SearchLimit::CostBound), true);
+                return None
+            }
+        };
+
+        // move the inconsistent nodes to the frontier, which is reordered by the next weight. The goal stays a candidate.
+        let weight = core::mem::replace(&mut state.weight, self.weights.next().unwrap_or_else(|| { self.done = true; S::one() }));
+        state.iteration += 1;
+        let open: Vec<_> = core::mem::take(&mut state.frontier).into_iter().map(|(node, _)| node)
+            .chain(state.incons.as_mut().unwrap().drain(..))
+            .chain([goal])
+            .collect();
+        let mut lower_bound = goal.cost.clone() + goal.estimate.clone();
+        for node in open {
+            if node.superseded.get() {
+                continue
+            }
+            let estimated_cost = node.cost.clone() + node.estimate.clone();
+            if estimated_cost < lower_bound {
+                lower_bound = estimated_cost;
+            }
+            state.frontier.push(node, node.cost.clone() + state.weight.clone() * node.estimate.clone());
+        }
+        self.search.stats.peak_frontier = self.search.stats.peak_frontier.max(state.frontier.len());
+        self.done |= lower_bound >= goal.cost;
+
+        Some(AnytimeSolution { path: goal.path().into_iter().cloned().collect(), cost: goal.cost.clone(), weight, lower_bound })
     }
 }
 
@@ -197,6 +821,231 @@ mod tests {
         assert_eq!(edit_distance.1, 11);
     }
 
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_shortest_path_goal_predicate() {
+        // a 10x10 grid with a wall at x = 5 except at y = 9; successors are an iterator and the closure counts expansions
+        let mut expanded = 0;
+        let successors = |&(x, y): &(i32, i32)| {
+            expanded += 1;
+            let neighbours = [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)];
+            Some(neighbours.into_iter()
+                .filter(|&(x, y)| (0..10).contains(&x) && (0..10).contains(&y) && (x != 5 || y == 9))
+                .map(|node| (node, 1)))
+        };
+
+        let (path, cost) = ShortestPath::new(successors)
+            .use_heuristic(|&(x, y): &(i32, i32)| (9 - x).abs() + y.abs())
+            .use_goal(|&node: &(i32, i32)| node == (9, 0))
+            .use_hash_map()
+            .solve([(0, 0)]).unwrap();
+        assert_eq!(cost, 9 + 9 + 9);
+        assert_eq!(path.len(), 28);
+        assert_eq!((path[0], path[27]), ((0, 0), (9, 0)));
+        assert!(expanded > 27);
+
+        // the goal is not reachable from a dead end
+        let dead_end = |_: &u32| None::<Vec<(u32, u32)>>;
+        assert!(ShortestPath::new(dead_end).use_goal(|&n| n == 1).use_btree_map().solve([0]).is_none());
+        let line = |&n: &u32| (n < 3).then(|| [(n + 1, 1)]);
+        assert_eq!(ShortestPath::new(line).use_goal(|&n| n == 1).use_btree_map().solve([0]), Some((vec![0, 1], 1)));
+        assert_eq!(ShortestPath::new(line).use_goal(|&n| n == 5).use_btree_map().solve([0]), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_shortest_path_key_and_stats() {
+        // nodes carry the moves that led to them, so equal positions are different nodes unless deduplicated by key
+        let successors = |(x, y, moves): &(u8, u8, Vec<char>)| {
+            let (x, y) = (*x, *y);
+            let step = |dx: u8, dy: u8, c: char| ((x + dx, y + dy, [&moves[..], &[c]].concat()), 1);
+            Some([(x < 7).then(|| step(1, 0, 'R')), (y < 7).then(|| step(0, 1, 'D'))].into_iter().flatten())
+        };
+
+        let mut search = ShortestPath::new(successors)
+            .use_goal(|node: &(u8, u8, Vec<char>)| (node.0, node.1) == (7, 7))
+            .use_key(|node: &(u8, u8, Vec<char>)| (node.0, node.1))
+            .use_hash_map();
+        let (path, cost) = search.solve([(0, 0, vec![])]).unwrap();
+        assert_eq!(cost, 14);
+        assert_eq!(path.last().unwrap().2.len(), 14);
+        let stats = search.stats();
+        assert!(stats.expanded <= 64);
+        assert!(stats.duplicates_pruned > 0);
+        assert_eq!(stats.generated, 1 + 2 * 7 * 8); // the initial node and every edge of the grid, at most once per node
+        assert!(stats.peak_frontier > 0 && stats.peak_frontier <= 2 * 8); // at most two diagonals
+
+        let mut without_key = ShortestPath::new(successors)
+            .use_goal(|node: &(u8, u8, Vec<char>)| (node.0, node.1) == (7, 7))
+            .use_btree_map();
+        assert_eq!(without_key.solve([(0, 0, vec![])]).unwrap().1, 14);
+        assert_eq!(without_key.stats().duplicates_pruned, 0);
+        assert!(without_key.stats().expanded > stats.expanded);
+
+        // the default key compares the nodes in place, so they need not be `Clone`
+        #[derive(Debug, PartialEq, Eq, Hash)]
+        struct Position(u8, u8);
+        let grid = |&Position(x, y): &Position| Some([(x < 7).then(|| (Position(x + 1, y), 1)), (y < 7).then(|| (Position(x, y + 1), 1))].into_iter().flatten());
+        let mut unique = ShortestPath::new(grid).use_goal(|node: &Position| *node == Position(7, 7)).use_hash_map();
+        assert_eq!(unique.solve([Position(0, 0)]).unwrap().1, 14);
+        assert_eq!(unique.stats(), stats);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_shortest_path_limits() {
+        // a 10x10 grid with a wall at x = 5 except at y = 9
+        let successors = |&(x, y): &(i32, i32)| {
+            let neighbours = [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)];
+            Some(neighbours.into_iter()
+                .filter(|&(x, y)| (0..10).contains(&x) && (0..10).contains(&y) && (x != 5 || y == 9))
+                .map(|node| (node, 1)))
+        };
+        let heuristic = |&(x, y): &(i32, i32)| (9 - x).abs() + y.abs();
+        let search = || ShortestPath::new(successors).use_heuristic(heuristic).use_goal(|&node| node == (9, 0)).use_hash_map();
+
+        let mut limited = search().use_max_expanded(10);
+        let SearchResult::LimitReached { limit: SearchLimit::Expanded, best_partial: Some((path, cost)) } = limited.search([(0, 0)]) else { panic!() };
+        assert_eq!(limited.stats().expanded, 10);
+        assert_eq!((path[0], path.len(), cost), ((0, 0), cost as usize + 1, cost));
+        assert!(heuristic(path.last().unwrap()) < heuristic(&(0, 0)));
+
+        assert_eq!(search().use_cost_bound(27).search([(0, 0)]).found().unwrap().1, 27);
+        let mut bounded = search().use_cost_bound(26);
+        assert!(matches!(bounded.search([(0, 0)]), SearchResult::LimitReached { limit: SearchLimit::CostBound, best_partial: Some(_) }));
+        assert!(bounded.stats().expanded < 50);
+
+        let mut late = search().use_deadline(std::time::Instant::now());
+        assert_eq!(late.search([(0, 0)]), SearchResult::LimitReached { limit: SearchLimit::Deadline, best_partial: None });
+        let mut early = search().use_deadline(std::time::Instant::now() + std::time::Duration::from_secs(60));
+        assert!(matches!(early.search([(0, 0)]), SearchResult::Found(_, 27)));
+
+        let line = |&n: &u32| (n < 3).then(|| [(n + 1, 1)]);
+        assert_eq!(ShortestPath::new(line).use_goal(|&n| n == 5).use_btree_map().search([0]), SearchResult::Exhausted);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_shortest_path_weighted_and_anytime() {
+        // a 30x30 grid where entering a cell costs 1 to 9
+        let cell_cost = |x: i32, y: i32| ((x * 7 + y * 13) ^ (x * y)).rem_euclid(9) + 1;
+        let successors = |&(x, y): &(i32, i32)| {
+            let neighbours = [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)];
+            Some(neighbours.into_iter()
+                .filter(|&(x, y)| (0..30).contains(&x) && (0..30).contains(&y))
+                .map(|(x, y)| ((x, y), cell_cost(x, y))))
+        };
+        let heuristic = |&(x, y): &(i32, i32)| (29 - x) + (29 - y);
+        let search = || ShortestPath::new(successors).use_heuristic(heuristic).use_goal(|&node| node == (29, 29)).use_hash_map();
+
+        let mut optimal_search = search();
+        let optimal = optimal_search.solve([(0, 0)]).unwrap().1;
+        let mut weighted_search = search().use_weight(4);
+        let weighted = weighted_search.solve([(0, 0)]).unwrap().1;
+        assert!(optimal <= weighted && weighted <= 4 * optimal);
+        assert!(weighted_search.stats().expanded < optimal_search.stats().expanded);
+
+        let mut anytime = search().use_anytime([4, 3, 2, 1]);
+        let solutions: Vec<_> = anytime.solutions([(0, 0)]).collect();
+        assert!(!solutions.is_empty() && solutions.len() <= 4);
+        assert_eq!(solutions[0].weight, 4);
+        let last = solutions.last().unwrap();
+        assert_eq!(last.cost, optimal);
+        assert!(last.weight == 1 || last.lower_bound >= last.cost); // the stream ends early only with a proof of optimality
+        for (solution, next) in solutions.iter().zip(&solutions[1..]) {
+            assert!(next.cost <= solution.cost && next.weight < solution.weight);
+            assert!(solution.lower_bound < solution.cost);
+        }
+        for solution in &solutions {
+            assert!(solution.cost <= solution.weight * optimal && solution.lower_bound <= optimal && solution.lower_bound <= solution.cost);
+            assert_eq!((solution.path[0], *solution.path.last().unwrap()), ((0, 0), (29, 29)));
+            let path_cost: i32 = solution.path[1..].iter().map(|&(x, y)| cell_cost(x, y)).sum();
+            assert_eq!(path_cost, solution.cost);
+        }
+
+        // with a perfect heuristic the first solution is proven optimal and ends the stream
+        let line = |&n: &u32| (n < 3).then(|| [(n + 1, 1)]);
+        let mut perfect = ShortestPath::new(line).use_heuristic(|&n| 3 - n).use_goal(|&n| n == 3).use_btree_map().use_anytime([4, 3, 2, 1]);
+        let solutions: Vec<_> = perfect.solutions([0]).collect();
+        assert_eq!(solutions, [AnytimeSolution { path: vec![0, 1, 2, 3], cost: 3, weight: 4, lower_bound: 3 }]);
+
+        // the stream drops its nodes, including those of the solutions, which are cloned
+        let counter = std::rc::Rc::new(());
+        let shared_line = |(n, rc): &(u32, std::rc::Rc<()>)| (*n < 3).then(|| [((n + 1, rc.clone()), 1)]);
+        let mut shared = ShortestPath::new(shared_line).use_goal(|node: &(u32, std::rc::Rc<()>)| node.0 == 3).use_btree_map().use_anytime([2, 1]);
+        let solutions: Vec<_> = shared.solutions([(0, counter.clone())]).collect();
+        assert_eq!(std::rc::Rc::strong_count(&counter), 1 + 4 * solutions.len());
+        drop(solutions);
+        assert_eq!(std::rc::Rc::strong_count(&counter), 1);
+
+        // a deadline that has passed ends the stream before the first solution
+        let mut late = search().use_anytime([4, 1]).use_deadline(std::time::Instant::now());
+        let mut stream = late.solutions([(0, 0)]);
+        assert!(stream.next().is_none());
+        assert_eq!(stream.stopped_by(), Some(SearchLimit::Deadline));
+
+        // X is expanded through S -> X, then improved through A and again through B in the same iteration;
+        // it waits for the next iteration both times instead of being expanded twice with weight 10
+        let x_expanded = Cell::new(0);
+        let problem = |&node: &char| match node {
+            'S' => Some(vec![('X', 10), ('A', 1), ('B', 1)]),
+            'A' => Some(vec![('X', 5)]),
+            'B' => Some(vec![('X', 1)]),
+            'X' => { x_expanded.set(x_expanded.get() + 1); Some(vec![('G', 100)]) }
+            _ => None,
+        };
+        let heuristic = |&node: &char| match node { 'X' => 1, 'A' => 2, 'B' => 3, _ => 0 };
+        let mut repairing = ShortestPath::new(problem).use_heuristic(heuristic).use_hash_map().use_anytime([10, 1]);
+        let costs: Vec<_> = repairing.solutions(['S']).map(|solution| (solution.cost, solution.weight)).collect();
+        assert_eq!(costs, [(110, 10), (102, 1)]);
+        assert_eq!(x_expanded.get(), 2);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_shortest_path_bidirectional() {
+        // a 30x30 grid where entering a cell costs 1 to 9, so the edges are directed
+        let cell_cost = |x: i32, y: i32| ((x * 7 + y * 13) ^ (x * y)).rem_euclid(9) + 1;
+        let neighbours = |x: i32, y: i32| [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].into_iter()
+            .filter(|&(x, y)| (0..30).contains(&x) && (0..30).contains(&y));
+        let successors = |&(x, y): &(i32, i32)| Some(neighbours(x, y).map(|(x, y)| ((x, y), cell_cost(x, y))));
+        let predecessors = |&(x, y): &(i32, i32)| neighbours(x, y).map(move |node| (node, cell_cost(x, y)));
+
+        for goals in [vec![(29, 29)], vec![(29, 29), (20, 3)], vec![(0, 0)]] {
+            let mut dijkstra = ShortestPath::new(successors).use_goal(|node| goals.contains(node)).use_hash_map();
+            let (_, optimal) = dijkstra.solve([(0, 0)]).unwrap();
+
+            let mut bidirectional = ShortestPath::new(successors).use_hash_map();
+            let (path, cost) = bidirectional.solve_bidirectional([(0, 0)], goals.clone(), predecessors).unwrap();
+            assert_eq!(cost, optimal);
+            assert_eq!(path[0], (0, 0));
+            assert!(goals.contains(path.last().unwrap()));
+            assert!(path.windows(2).all(|w| w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1) == 1));
+            assert_eq!(path[1..].iter().map(|&(x, y)| cell_cost(x, y)).sum::<i32>(), cost);
+            assert!(bidirectional.stats().expanded <= dijkstra.stats().expanded);
+        }
+
+        let mut walled = ShortestPath::new(|&n: &u32| Some([(n + 1, 1)].into_iter().filter(|&(n, _)| n < 5))).use_btree_map();
+        assert_eq!(walled.solve_bidirectional([0], [3], |&n: &u32| [(n - 1, 1)].into_iter().filter(move |_| n > 0)), Some((vec![0, 1, 2, 3], 3)));
+        assert_eq!(walled.solve_bidirectional([0], [7], |&n: &u32| [(n - 1, 1)].into_iter().filter(move |_| n > 6)), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_shortest_path_heuristic_of_children() {
+        // S -> P -> G costs 10 and S -> R -> G costs 15; ordering the children by the heuristic of S finds the latter first
+        let problem = |&node: &char| match node {
+            'S' => Some(vec![('P', 0), ('R', 14)]),
+            'P' => Some(vec![('G', 10)]),
+            'R' => Some(vec![('G', 1)]),
+            _ => None,
+        };
+        let heuristic = |&node: &char| match node { 'P' => 10, 'R' => 1, _ => 0 };
+
+        let result = ShortestPath::new(problem).use_heuristic(heuristic).use_hash_map().solve(['S']);
+        assert_eq!(result, Some((vec!['S', 'P', 'G'], 10))); // the start node appears once
+    }
+
     #[test]
     fn test_binary_search() {
         let f = |x: &f64| x * x * x + x > 5.0;
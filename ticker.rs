@@ -83,3 +83,279 @@ impl RunningAverage {
         core::mem::take(self).get()
     }
 }
+
+/// a mergeable sketch of the distribution of a stream, answering quantile and cdf queries (DDSketch, Masson, Rim and Lee, 2019).
+/// values are counted in logarithmic buckets of ratio gamma = (1 + a) / (1 - a), where a is the relative accuracy,
+/// so every quantile is returned within a relative error of a: |estimate - exact| <= a * |exact|, with the exact quantile
+/// taken at rank floor(q * (count - 1)). memory is bounded by `max_bins` buckets per sign; when exceeded, the buckets
+/// of the smallest magnitudes are collapsed and only quantiles whose values fall within a factor gamma^max_bins of the
+/// largest magnitude of their sign keep the guarantee. with the default a = 1% and 2048 buckets, that range spans
+/// 17 orders of magnitude.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct QuantileSketch {
+    relative_accuracy: f64,
+    gamma_ln: f64,
+    max_bins: usize,
+    negative: QuantileBins, // keyed by the bucket index of |x| like `positive`, so keys descend with the values
+    positive: QuantileBins,
+    zero: usize,
+    count: usize,
+    min: f64,
+    max: f64,
+}
+
+#[cfg(feature = "std")]
+#[derive(Clone, Default)]
+struct QuantileBins {
+    offset: i64,
+    counts: std::collections::VecDeque<usize>,
+}
+
+#[cfg(feature = "std")]
+impl QuantileBins {
+    // the retained keys span at most `max_bins`; the buckets below are collapsed into the lowest one before extending
+    fn add(&mut self, key: i64, n: usize, max_bins: usize) {
+        let high = match self.counts.is_empty() {
+            true => key,
+            false => (self.offset + self.counts.len() as i64 - 1).max(key),
+        };
+        let low = high - (max_bins as i64 - 1);
+        let key = key.max(low); // keys below the retained range go to the lowest bucket
+
+        let mut collapsed = 0;
+        while !self.counts.is_empty() && self.offset < low {
+            collapsed += self.counts.pop_front().unwrap();
+            self.offset += 1;
+        }
+        if self.counts.is_empty() {
+            self.offset = if collapsed > 0 { low } else { key };
+        }
+
+        while key < self.offset {
+            self.counts.push_front(0);
+            self.offset -= 1;
+        }
+        while key >= self.offset + self.counts.len() as i64 {
+            self.counts.push_back(0);
+        }
+        self.counts[0] += collapsed;
+        self.counts[(key - self.offset) as usize] += n;
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = (i64, usize)> + '_ {
+        self.counts.iter().enumerate().filter(|&(_, &n)| n > 0).map(|(i, &n)| (self.offset + i as i64, n))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for QuantileSketch {
+    fn default() -> Self {
+        Self::new(0.01, 2048)
+    }
+}
+
+#[cfg(feature = "std")]
+impl QuantileSketch {
+    /// `relative_accuracy` must be in (0, 1).
+    pub fn new(relative_accuracy: f64, max_bins: usize) -> Self {
+        assert!(relative_accuracy > 0. && relative_accuracy < 1. && max_bins > 0);
+        Self {
+            relative_accuracy,
+            gamma_ln: ((1. + relative_accuracy) / (1. - relative_accuracy)).ln(),
+            max_bins,
+            negative: Default::default(),
+            positive: Default::default(),
+            zero: 0,
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    fn key(&self, x: f64) -> i64 {
+        (x.ln() / self.gamma_ln).ceil() as i64
+    }
+
+    // the value in the middle of the bucket, within the relative accuracy of every value in it
+    fn value(&self, key: i64) -> f64 {
+        2. * (key as f64 * self.gamma_ln).exp() / (1. + self.gamma_ln.exp())
+    }
+
+    pub fn observe(&mut self, x: f64) {
+        self.observe_n(x, 1)
+    }
+
+    /// observe `x` for `n` times. NaN and infinities are ignored.
+    pub fn observe_n(&mut self, x: f64, n: usize) {
+        if !x.is_finite() || n == 0 {
+            return
+        }
+
+        if x >= f64::MIN_POSITIVE {
+            self.positive.add(self.key(x), n, self.max_bins);
+        } else if x <= -f64::MIN_POSITIVE {
+            self.negative.add(self.key(-x), n, self.max_bins);
+        } else {
+            self.zero += n;
+        }
+        self.count += n;
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+    }
+
+    /// merge another sketch with the same relative accuracy into this one.
+    pub fn merge(&mut self, other: &Self) {
+        assert_eq!(self.relative_accuracy, other.relative_accuracy);
+        for (key, n) in other.negative.iter() {
+            self.negative.add(key, n, self.max_bins);
+        }
+        for (key, n) in other.positive.iter() {
+            self.positive.add(key, n, self.max_bins);
+        }
+        self.zero += other.zero;
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    // the (value, count) of the buckets in ascending order
+    fn buckets(&self) -> impl Iterator<Item = (f64, usize)> + '_ {
+        let negative = self.negative.iter().rev().map(|(key, n)| (-self.value(key), n));
+        let positive = self.positive.iter().map(|(key, n)| (self.value(key), n));
+        negative.chain([(0., self.zero)]).chain(positive)
+    }
+
+    /// return the estimated value at quantile `q` in [0, 1], or None if the sketch is empty
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None
+        }
+
+        let rank = (q.clamp(0., 1.) * (self.count - 1) as f64) as usize;
+        if rank == 0 {
+            return Some(self.min)
+        }
+        if rank == self.count - 1 {
+            return Some(self.max)
+        }
+
+        let mut seen = 0;
+        let (value, _) = self.buckets().find(|&(_, n)| { seen += n; seen > rank })?;
+        Some(value.clamp(self.min, self.max))
+    }
+
+    /// return the estimated fraction of observations less than or equal to `x`
+    pub fn cdf(&self, x: f64) -> f64 {
+        if self.count == 0 || x < self.min {
+            return 0.
+        }
+        if x >= self.max {
+            return 1.
+        }
+
+        let below = if x >= f64::MIN_POSITIVE {
+            let key = self.key(x);
+            self.negative.iter().map(|(_, n)| n).sum::<usize>() + self.zero + self.positive.iter().filter(|&(k, _)| k <= key).map(|(_, n)| n).sum::<usize>()
+        } else if x <= -f64::MIN_POSITIVE {
+            let key = self.key(-x);
+            self.negative.iter().filter(|&(k, _)| k >= key).map(|(_, n)| n).sum()
+        } else {
+            self.negative.iter().map(|(_, n)| n).sum::<usize>() + self.zero
+        };
+        below as f64 / self.count as f64
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    pub fn relative_accuracy(&self) -> f64 {
+        self.relative_accuracy
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantile_sketch() {
+        let mut sketch = QuantileSketch::default();
+        assert_eq!(sketch.quantile(0.5), None);
+
+        for i in 1..=100000 {
+            sketch.observe(i as f64);
+        }
+        assert_eq!(sketch.count(), 100000);
+        assert_eq!((sketch.min(), sketch.max()), (Some(1.), Some(100000.)));
+        for (q, exact) in [(0.5, 50000.), (0.95, 95000.), (0.99, 99000.)] {
+            assert!((sketch.quantile(q).unwrap() - exact).abs() <= 0.01 * exact);
+        }
+        assert_eq!(sketch.quantile(0.), Some(1.));
+        assert_eq!(sketch.quantile(1.), Some(100000.));
+        assert!((sketch.cdf(50000.) - 0.5).abs() < 0.01);
+        assert_eq!((sketch.cdf(0.), sketch.cdf(1e9)), (0., 1.));
+
+        let mut signed = QuantileSketch::new(0.02, 128);
+        [-100., -10., -1., 0., 0., 1., 10., 100.].into_iter().for_each(|x| signed.observe(x));
+        signed.observe(f64::NAN);
+        assert_eq!(signed.count(), 8);
+        assert_eq!(signed.quantile(0.), Some(-100.));
+        assert!((signed.quantile(0.3).unwrap() + 1.).abs() <= 0.021);
+        assert_eq!(signed.quantile(0.5), Some(0.));
+        assert!((signed.quantile(0.9).unwrap() - 10.).abs() <= 0.2);
+        assert_eq!(signed.cdf(-5.), 0.25);
+        assert_eq!(signed.cdf(0.), 0.625);
+    }
+
+    #[test]
+    fn test_quantile_sketch_merge_and_collapse() {
+        let (mut a, mut b, mut whole) = (QuantileSketch::default(), QuantileSketch::default(), QuantileSketch::default());
+        for i in 0..10000 {
+            let x = (i * 7919 % 10007) as f64 / 10.;
+            if i % 3 == 0 { a.observe(x) } else { b.observe(x) }
+            whole.observe(x);
+        }
+        a.merge(&b);
+        assert_eq!(a.count(), whole.count());
+        assert_eq!((a.min(), a.max()), (whole.min(), whole.max()));
+        for q in [0., 0.1, 0.5, 0.9, 0.999, 1.] {
+            assert_eq!(a.quantile(q), whole.quantile(q));
+        }
+
+        // values from 1e-3 to 1e6 need about 1036 buckets at 1% accuracy
+        let mut small = QuantileSketch::new(0.01, 200);
+        for i in 0..=9000 {
+            small.observe(10f64.powf(i as f64 / 1000. - 3.));
+        }
+        let p99 = 10f64.powf(8910. / 1000. - 3.);
+        assert!((small.quantile(0.99).unwrap() - p99).abs() <= 0.01 * p99);
+        assert!(small.quantile(0.01).unwrap() > 10f64.powf(0.09 - 3.)); // collapsed into a higher bucket
+        assert_eq!(small.quantile(0.), Some(1e-3));
+
+        // the extremes stay within `max_bins` buckets and non-finite values are ignored
+        let mut wide = QuantileSketch::new(0.01, 64);
+        for x in [1e-300, 1e300, -1e-300, -1e300, f64::INFINITY, f64::NEG_INFINITY] {
+            wide.observe(x);
+        }
+        assert_eq!(wide.count(), 4);
+        assert!(wide.positive.counts.len() <= 64 && wide.negative.counts.len() <= 64);
+        assert!((wide.quantile(1.).unwrap() - 1e300).abs() <= 0.01 * 1e300);
+        assert!((wide.quantile(0.).unwrap() + 1e300).abs() <= 0.01 * 1e300);
+        assert!(wide.quantile(0.4).unwrap() < -1e-300 && wide.quantile(0.7).unwrap() > 1e-300); // smallest magnitudes collapse
+    }
+}